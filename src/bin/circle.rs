@@ -1,7 +1,7 @@
 use raytracer::graphics::canvas::Canvas;
 use raytracer::graphics::color;
 use raytracer::graphics::color::Color;
-use raytracer::graphics::lights::PointLight;
+use raytracer::graphics::lights::Light;
 use raytracer::graphics::materials::Material;
 use raytracer::graphics::ppm;
 use raytracer::math::point::Point;
@@ -25,7 +25,7 @@ pub fn main() {
         ..Default::default()
     };
 
-    let light = PointLight::new(Point::new(-5.0, 10.0, -15.0), Color::new(1.0, 1.0, 1.0));
+    let light = Light::new(Point::new(-5.0, 10.0, -15.0), Color::new(1.0, 1.0, 1.0));
 
     let wall_z = 10.0;
     let wall_size = 7.0;
@@ -59,7 +59,7 @@ pub fn main() {
 
             let color = sphere
                 .material
-                .lighting(sphere, light, hit_point, eye, normal, false);
+                .lighting(sphere, light, hit_point, eye, normal, color::WHITE);
             canvas.write(x, y, color);
         }
     }
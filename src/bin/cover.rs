@@ -4,7 +4,7 @@ use raytracer::{
     graphics::{
         camera::Camera,
         color::{self, Color},
-        lights::PointLight,
+        lights::Light,
         materials::Material,
         ppm,
     },
@@ -24,10 +24,10 @@ fn main() {
     );
     let mut world = World::new();
 
-    world.light = Some(PointLight::new(
+    world.lights = vec![Light::new(
         Point::new(50.0, 100.0, -50.0),
         Color::new(0.9, 0.9, 0.9),
-    ));
+    )];
 
     // =======================================
     // define constants to avoid duplication
@@ -4,7 +4,7 @@ use raytracer::{
     graphics::{
         camera::Camera,
         color::{self, Color},
-        lights::PointLight,
+        lights::Light,
         patterns::{Pattern, PatternType},
         ppm,
     },
@@ -99,10 +99,10 @@ fn main() {
     ));
 
     let mut world = World::new();
-    world.light = Some(PointLight::new(
+    world.lights = vec![Light::new(
         Point::new(-10.0, 10.0, -10.0),
         Color::new(1.0, 1.0, 1.0),
-    ));
+    )];
     world.objects = vec![
         floor, // left_wall, right_wall,
         air, middle, right, left,
@@ -4,8 +4,9 @@ use raytracer::{
     graphics::{
         camera::Camera,
         color::{self, Color},
-        lights::PointLight,
+        lights::Light,
         patterns::{Pattern, PatternType},
+        photon_map::PhotonMap,
         ppm,
     },
     math::{point::Point, transformations::Transformation, vector::Vector},
@@ -56,14 +57,16 @@ fn main() {
     air.material.color = Color::new(1.0, 1.0, 1.0);
 
     let mut world = World::new();
-    world.light = Some(PointLight::new(
+    world.lights = vec![Light::new(
         Point::new(2.0, 10.0, -5.0),
         Color::new(0.9, 0.9, 0.9),
-    ));
+    )];
     world.objects = vec![
         floor, // left_wall, right_wall,
         glass, air,
     ];
+    // light focused by the glass
+    world.photon_map = Some(PhotonMap::build(&world, 200_000, 0.05));
 
     // let mut camera = Camera::new(1920, 1080, FRAC_PI_3);
     // let mut camera = Camera::new(3840, 2160, FRAC_PI_3);
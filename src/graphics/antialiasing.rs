@@ -0,0 +1,191 @@
+use super::{canvas::Canvas, color::Color};
+use crate::math::sampling::{self, Pcg};
+
+/// How sample positions are distributed inside a pixel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SamplePattern {
+    /// Regular `k x k` grid, `k = ceil(sqrt(samples))`
+    Grid,
+    /// Same grid as `Grid`, but every sample is moved randomly inside its cell
+    Jittered,
+    /// Halton sequence (bases 2 and 3), randomly shifted per pixel
+    Halton,
+}
+
+/// Reconstruction filter, weights each sample by its distance from the pixel center.
+/// Distances are measured in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    /// Every sample inside the pixel counts the same, radius `0.5`
+    Box,
+    /// Linear falloff, radius `1.0`
+    Tent,
+    /// Gaussian bell (alpha `2.0`), radius `1.5`
+    Gaussian,
+    /// Mitchell-Netravali cubic (B = C = 1/3), radius `2.0`.
+    /// Has negative lobes, which sharpen edges slightly
+    Mitchell,
+}
+
+impl SamplePattern {
+    /// Number of positions returned by `positions` for `samples`
+    pub fn count(&self, samples: usize) -> usize {
+        let samples = samples.max(1);
+        match self {
+            SamplePattern::Grid | SamplePattern::Jittered => {
+                let k = (samples as f64).sqrt().ceil() as usize;
+                k * k
+            }
+            SamplePattern::Halton => samples,
+        }
+    }
+
+    /// Returns sample positions in `[0, 1) x [0, 1)`.
+    /// `Grid` and `Jittered` round `samples` up to a perfect square.
+    /// `rng` is only used by the random patterns
+    pub fn positions(&self, samples: usize, rng: &mut Pcg) -> Vec<(f64, f64)> {
+        let samples = samples.max(1);
+        match self {
+            SamplePattern::Grid => grid(samples, || (0.5, 0.5)),
+            SamplePattern::Jittered => grid(samples, || (rng.next_f64(), rng.next_f64())),
+            SamplePattern::Halton => {
+                // Cranley-Patterson rotation, so neighbouring pixels don't share positions
+                let (shift_u, shift_v) = (rng.next_f64(), rng.next_f64());
+                (0..samples as u64)
+                    .map(|i| {
+                        let u = sampling::radical_inverse(2, i + 1) + shift_u;
+                        let v = sampling::radical_inverse(3, i + 1) + shift_v;
+                        (u.fract(), v.fract())
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// `k x k` grid, `offset` returns the position inside each cell
+fn grid(samples: usize, mut offset: impl FnMut() -> (f64, f64)) -> Vec<(f64, f64)> {
+    let k = (samples as f64).sqrt().ceil() as usize;
+    let cell = 1.0 / k as f64;
+
+    (0..k * k)
+        .map(|i| {
+            let (du, dv) = offset();
+            let u = ((i % k) as f64 + du) * cell;
+            let v = ((i / k) as f64 + dv) * cell;
+            (u, v)
+        })
+        .collect()
+}
+
+impl Filter {
+    /// Half width of the filter in pixels, samples are spread over this area
+    pub fn radius(&self) -> f64 {
+        match self {
+            Filter::Box => 0.5,
+            Filter::Tent => 1.0,
+            Filter::Gaussian => 1.5,
+            Filter::Mitchell => 2.0,
+        }
+    }
+
+    /// Weight of a sample at (`dx`, `dy`) pixels from the pixel center
+    pub fn weight(&self, dx: f64, dy: f64) -> f64 {
+        self.weight_1d(dx) * self.weight_1d(dy)
+    }
+
+    /// All filters are separable
+    fn weight_1d(&self, d: f64) -> f64 {
+        let d = d.abs();
+        let radius = self.radius();
+
+        if d > radius {
+            return 0.0;
+        }
+        match self {
+            Filter::Box => 1.0,
+            Filter::Tent => radius - d,
+            Filter::Gaussian => {
+                let alpha = 2.0;
+                (-alpha * d * d).exp() - (-alpha * radius * radius).exp()
+            }
+            Filter::Mitchell => mitchell(d / radius * 2.0, 1.0 / 3.0, 1.0 / 3.0),
+        }
+    }
+}
+
+/// Mitchell-Netravali cubic, `x` in `[0, 2]`
+fn mitchell(x: f64, b: f64, c: f64) -> f64 {
+    let x2 = x * x;
+    let x3 = x2 * x;
+
+    if x < 1.0 {
+        ((12.0 - 9.0 * b - 6.0 * c) * x3 + (-18.0 + 12.0 * b + 6.0 * c) * x2 + (6.0 - 2.0 * b))
+            / 6.0
+    } else {
+        ((-b - 6.0 * c) * x3
+            + (6.0 * b + 30.0 * c) * x2
+            + (-12.0 * b - 48.0 * c) * x
+            + (8.0 * b + 24.0 * c))
+            / 6.0
+    }
+}
+
+/// Adaptive supersampling, each pixel is first sampled at its corners and center,
+/// then split into quadrants recursively while its samples, or the pixel and its
+/// neighbours, differ by more than `threshold`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Adaptive {
+    /// largest difference in any color channel that is still considered flat
+    pub threshold: f64,
+    /// how many times a pixel can be split, each level splits into 4 quadrants
+    pub max_depth: usize,
+}
+
+impl Default for Adaptive {
+    fn default() -> Self {
+        Self {
+            threshold: 0.1,
+            max_depth: 2,
+        }
+    }
+}
+
+impl Adaptive {
+    /// true when `colors` differ by more than `threshold` in any channel
+    pub fn differ(&self, colors: &[Color]) -> bool {
+        let range = |channel: fn(&Color) -> f64| {
+            let values = colors.iter().map(channel);
+            let max = values.clone().fold(f64::MIN, f64::max);
+            let min = values.fold(f64::MAX, f64::min);
+            max - min
+        };
+        range(|c| c.red) > self.threshold
+            || range(|c| c.green) > self.threshold
+            || range(|c| c.blue) > self.threshold
+    }
+}
+
+/// Rays traced for every pixel, row major like `Canvas::grid`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleCounts {
+    pub width: usize,
+    pub height: usize,
+    pub counts: Vec<usize>,
+}
+
+impl SampleCounts {
+    /// Debug image, black is the fewest samples and white the most
+    pub fn to_canvas(&self) -> Canvas {
+        let min = self.counts.iter().min().copied().unwrap_or(0);
+        let max = self.counts.iter().max().copied().unwrap_or(0);
+        let range = (max - min).max(1) as f64;
+
+        let mut canvas = Canvas::new(self.width, self.height);
+        self.counts.iter().enumerate().for_each(|(i, count)| {
+            let v = (count - min) as f64 / range;
+            canvas.write_i(i, Color::new(v, v, v));
+        });
+        canvas
+    }
+}
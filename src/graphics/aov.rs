@@ -0,0 +1,153 @@
+use crate::{
+    math::{ray::Ray, sampling::Pcg},
+    objects::world::World,
+};
+
+use super::{
+    canvas::Canvas,
+    color::{self, Color},
+    integrators::{self, Integrators},
+};
+
+/// Arbitrary output variable, a render pass holding one property of the
+/// first surface hit. Rays that miss leave every pass black, except `Beauty`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Aov {
+    /// the final image, same as `Camera::render`
+    Beauty,
+    /// distance from the camera in world units, in every channel (not clamped)
+    Depth,
+    /// world space normal facing the camera, `x`, `y` and `z` in red, green and blue
+    Normal,
+    /// surface color without lighting, `Material::color` or its pattern
+    Albedo,
+    /// a random flat color for every object
+    ObjectId,
+    /// light blocked on the way to the light, white in full shadow,
+    /// tinted behind colored glass
+    Shadow,
+    /// direct light on the surface, see `Shading::surface`
+    Surface,
+    /// see `Shading::reflected`
+    Reflection,
+    /// see `Shading::refracted`
+    Refraction,
+}
+
+impl Aov {
+    /// every pass, in the order above
+    pub const ALL: [Aov; 9] = [
+        Aov::Beauty,
+        Aov::Depth,
+        Aov::Normal,
+        Aov::Albedo,
+        Aov::ObjectId,
+        Aov::Shadow,
+        Aov::Surface,
+        Aov::Reflection,
+        Aov::Refraction,
+    ];
+
+    /// name of the pass, used for file names and lookups
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aov::Beauty => "beauty",
+            Aov::Depth => "depth",
+            Aov::Normal => "normal",
+            Aov::Albedo => "albedo",
+            Aov::ObjectId => "object_id",
+            Aov::Shadow => "shadow",
+            Aov::Surface => "surface",
+            Aov::Reflection => "reflection",
+            Aov::Refraction => "refraction",
+        }
+    }
+
+    /// Returns the value of every pass in `aovs` for `ray`, in the same order.
+    /// The surface is shaded once, with `World::shade_hit_components`,
+    /// `Beauty` is traced again only when `world.settings.integrator` isn't `Integrators::Whitted`,
+    /// or in spectral mode
+    pub fn sample(aovs: &[Aov], world: &World, ray: Ray, rng: &mut Pcg) -> Vec<Color> {
+        let integrator = world.settings.integrator;
+        let xs = world.intersect(ray);
+        let comps = match xs
+            .hit()
+            .and_then(|hit| hit.prepare_computations(ray, Some(&xs)))
+        {
+            Some(comps) => comps,
+            None => {
+                return aovs
+                    .iter()
+                    .map(|aov| match aov {
+                        Aov::Beauty => {
+                            let (transmittance, scattered) = world.participate(ray, f64::INFINITY);
+                            world.settings.background * transmittance + scattered
+                        }
+                        _ => color::BLACK,
+                    })
+                    .collect()
+            }
+        };
+
+        let needs_shading = aovs.iter().any(|aov| {
+            matches!(
+                aov,
+                Aov::Beauty | Aov::Shadow | Aov::Surface | Aov::Reflection | Aov::Refraction
+            )
+        });
+        // same depth as `World::color_at`
+        let shading =
+            needs_shading.then(|| world.shade_hit_components(&comps, world.settings.max_depth - 1));
+        let shading = shading.as_ref();
+        let gray = |v: f64| Color::new(v, v, v);
+
+        aovs.iter()
+            .map(|aov| match aov {
+                Aov::Beauty => match (integrator, shading) {
+                    (Integrators::Whitted(_), Some(shading)) if !world.settings.spectral => {
+                        // fog in front of the hit, as in `World::color_at`
+                        let (transmittance, scattered) =
+                            world.participate(ray, comps.intersects_at);
+                        shading.total() * comps.transmittance() * transmittance + scattered
+                    }
+                    _ => integrators::trace(world, ray, rng),
+                },
+                Aov::Depth => gray(comps.intersects_at * ray.direction.magnitude()),
+                Aov::Normal => {
+                    let n = comps.normalv;
+                    Color::new(n.x, n.y, n.z)
+                }
+                Aov::Albedo => comps.object.material.color_at(&comps.object, comps.point),
+                Aov::ObjectId => integrators::id_color(comps.object.uid),
+                Aov::Shadow => shading.map_or(color::BLACK, |s| color::WHITE - s.light_attenuation),
+                Aov::Surface => shading.map_or(color::BLACK, |s| s.surface),
+                Aov::Reflection => shading.map_or(color::BLACK, |s| s.reflected),
+                Aov::Refraction => shading.map_or(color::BLACK, |s| s.refracted),
+            })
+            .collect()
+    }
+}
+
+/// Named buffers filled by `Camera::render_aovs`, one `Canvas` per `Aov`
+#[derive(Debug, Clone)]
+pub struct RenderPasses {
+    pub passes: Vec<(Aov, Canvas)>,
+}
+
+impl RenderPasses {
+    /// the `Canvas` for `aov`, `None` if it wasn't rendered
+    pub fn get(&self, aov: Aov) -> Option<&Canvas> {
+        self.passes
+            .iter()
+            .find(|(pass, _)| *pass == aov)
+            .map(|(_, canvas)| canvas)
+    }
+
+    /// the `Canvas` for the pass called `name`, see `Aov::name`
+    pub fn get_by_name(&self, name: &str) -> Option<&Canvas> {
+        self.passes
+            .iter()
+            .find(|(pass, _)| pass.name() == name)
+            .map(|(_, canvas)| canvas)
+    }
+}
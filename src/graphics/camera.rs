@@ -1,12 +1,27 @@
 // use rayon::prelude::*;
 
+use std::{f64::consts::PI, time::Instant};
+
 use crate::{
-    constants,
-    math::{point::Point, ray::Ray, transformations::Transformation},
+    math::{
+        point::{self, Point},
+        ray::Ray,
+        sampling::{self, Pcg},
+        transformations::Transformation,
+        vector::Vector,
+    },
     objects::world::World,
 };
 
-use super::canvas::Canvas;
+use super::{
+    antialiasing::{Adaptive, Filter, SampleCounts, SamplePattern},
+    aov::{Aov, RenderPasses},
+    canvas::Canvas,
+    color::{self, Color},
+    control::{PartialRender, RenderControl},
+    integrators::{self, Integrator},
+    settings::RenderSettings,
+};
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 /// Camera has the canvas always one unit away.
@@ -25,6 +40,70 @@ pub struct Camera {
     pub half_width: f64,
     /// half the height of the canvas
     pub half_height: f64,
+    /// where the rays pass through the pixel, defaults to `SamplePattern::Grid`
+    pub sample_pattern: SamplePattern,
+    /// how samples are weighted into the pixel, defaults to `Filter::Box`
+    pub filter: Filter,
+    /// when set, replaces `RenderSettings::samples`, `sample_pattern` and `filter`
+    /// with adaptive supersampling, defaults to `None`
+    pub adaptive: Option<Adaptive>,
+    /// radius of the lens in world units, `0.0` (default) is a pinhole camera
+    /// where everything is in focus
+    pub aperture: f64,
+    /// shape of the lens opening, shapes the out of focus highlights (bokeh)
+    pub aperture_shape: Aperture,
+    /// distance from the camera to the plane that is in perfect focus, defaults to `1.0`
+    pub focal_distance: f64,
+    /// rays are cast at random times between `shutter_open` and `shutter_close`,
+    /// animated objects are blurred along their motion. Both default to `0.0`
+    pub shutter_open: f64,
+    /// see `shutter_open`
+    pub shutter_close: f64,
+    /// how rays leave the camera, defaults to `Projection::Perspective`
+    pub projection: Projection,
+    /// horizontal offset of the canvas (one unit away) from the view direction,
+    /// an off-axis `Perspective` used by parallel stereo rigs. Defaults to `0.0`
+    pub shift_x: f64,
+}
+
+/// How the canvas is mapped to rays
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Projection {
+    /// pinhole (or thin lens), rays spread out from the camera using `fov`
+    Perspective,
+    /// parallel rays, for technical and architectural views.
+    /// `width` is the width of the view in world units, `fov` is unused
+    Orthographic { width: f64 },
+    /// 360 degrees around and 180 degrees up and down (latitude/longitude),
+    /// use a 2:1 canvas, `fov` is unused
+    Equirectangular,
+    /// equidistant fisheye, `fov` is the angle across the image circle
+    /// and can go above 180 degrees. Pixels outside the circle are black
+    Fisheye,
+}
+
+/// Shape of the lens opening
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Aperture {
+    Circle,
+    /// regular polygon formed by `blades` (at least 3),
+    /// first corner is at `rotation` radians
+    Polygon {
+        blades: usize,
+        rotation: f64,
+    },
+}
+
+impl Aperture {
+    /// Maps `u` and `v` in `[0, 1)` to a point on the aperture, with radius `1.0`
+    pub fn sample(&self, u: f64, v: f64) -> (f64, f64) {
+        match *self {
+            Aperture::Circle => sampling::concentric_disk(u, v),
+            Aperture::Polygon { blades, rotation } => {
+                sampling::regular_polygon(blades, rotation, u, v)
+            }
+        }
+    }
 }
 
 impl Camera {
@@ -41,6 +120,16 @@ impl Camera {
             pixel_size,
             half_width,
             half_height,
+            sample_pattern: SamplePattern::Grid,
+            filter: Filter::Box,
+            adaptive: None,
+            aperture: 0.0,
+            aperture_shape: Aperture::Circle,
+            focal_distance: 1.0,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
+            projection: Projection::Perspective,
+            shift_x: 0.0,
         }
     }
 
@@ -67,57 +156,606 @@ impl Camera {
 
     /// returns a new ray starting at camera and passing through pixel on canvas
     /// x and y indicate pixel position.
-    fn ray_for_pixel(&self, x: usize, y: usize) -> Ray {
-        // offset from the edge of the canvas to the pixel's center
-        let xoffset = (x as f64 + 0.5) * self.pixel_size;
-        let yoffset = (y as f64 + 0.5) * self.pixel_size;
+    /// `None` if the pixel is outside of the projection (corners of a `Fisheye`)
+    fn ray_for_pixel(&self, x: usize, y: usize) -> Option<Ray> {
+        // the pixel's center
+        self.ray_for_film(x as f64 + 0.5, y as f64 + 0.5)
+    }
+
+    /// returns a new ray starting at camera and passing through the canvas at (`x`, `y`)
+    /// x and y are in pixels, measured from the top left edge of the canvas
+    fn ray_for_film(&self, x: f64, y: f64) -> Option<Ray> {
+        self.ray_through_lens(x, y, 0.0, 0.0)
+    }
+
+    /// same as `ray_for_film`, but starts from a random point on the lens
+    /// when the camera has an `aperture`, at a random time while the shutter is open
+    fn ray_for_sample(&self, x: f64, y: f64, rng: &mut Pcg) -> Option<Ray> {
+        let mut ray = if self.aperture > 0.0 {
+            let (lens_x, lens_y) = self.aperture_shape.sample(rng.next_f64(), rng.next_f64());
+            self.ray_through_lens(x, y, lens_x * self.aperture, lens_y * self.aperture)
+        } else {
+            self.ray_for_film(x, y)
+        }?;
+
+        ray.time = if self.shutter_close > self.shutter_open {
+            self.shutter_open + rng.next_f64() * (self.shutter_close - self.shutter_open)
+        } else {
+            self.shutter_open
+        };
+        Some(ray)
+    }
+
+    /// ray from (`lens_x`, `lens_y`) on the lens, passing through the focal plane
+    /// where the ray through the lens center and the canvas at (`x`, `y`) meets it.
+    /// The lens is only used by the `Perspective` projection
+    fn ray_through_lens(&self, x: f64, y: f64, lens_x: f64, lens_y: f64) -> Option<Ray> {
+        // origin and a second point on the ray, in camera space
+        let (origin, target) = match self.projection {
+            Projection::Perspective => {
+                // offset from the edge of the canvas
+                let xoffset = x * self.pixel_size;
+                let yoffset = y * self.pixel_size;
+
+                // untransformed coordinates of the pixel in world space.
+                let world_x = self.half_width - xoffset + self.shift_x;
+                let world_y = self.half_height - yoffset;
+
+                // the canvas is one unit away, scale it onto the focal plane
+                let focus = self.focal_distance;
+                let focus_point = Point::new(world_x * focus, world_y * focus, -focus);
+                (Point::new(lens_x, lens_y, 0.0), focus_point)
+            }
+            Projection::Orthographic { width } => {
+                let pixel_size = width / self.hsize as f64;
+                let world_x = width / 2.0 - x * pixel_size;
+                let world_y = self.vsize as f64 * pixel_size / 2.0 - y * pixel_size;
+
+                // parallel rays, all looking down -z
+                let origin = Point::new(world_x, world_y, 0.0);
+                (origin, Point::new(world_x, world_y, -1.0))
+            }
+            Projection::Equirectangular => {
+                // longitude, zero at the center, positive to the left
+                let phi = (0.5 - x / self.hsize as f64) * 2.0 * PI;
+                // latitude, zero at the center, positive up
+                let theta = (0.5 - y / self.vsize as f64) * PI;
 
-        // untransformed coordinates of the pixel in world space.
-        let world_x = self.half_width - xoffset;
-        let world_y = self.half_height - yoffset;
+                let direction = Vector::new(
+                    theta.cos() * phi.sin(),
+                    theta.sin(),
+                    -theta.cos() * phi.cos(),
+                );
+                (point::ORIGIN, point::ORIGIN + direction)
+            }
+            Projection::Fisheye => {
+                // distance from the center, 1.0 is the edge of the image circle
+                let radius = self.hsize.min(self.vsize) as f64 / 2.0;
+                let nx = (self.hsize as f64 / 2.0 - x) / radius;
+                let ny = (self.vsize as f64 / 2.0 - y) / radius;
+                let r = (nx * nx + ny * ny).sqrt();
+
+                if r > 1.0 {
+                    return None;
+                }
+
+                // angle from the view direction grows linearly (equidistant fisheye)
+                let theta = r * self.fov / 2.0;
+                let (sin, cos) = theta.sin_cos();
+                let direction = if r > 0.0 {
+                    Vector::new(sin * nx / r, sin * ny / r, -cos)
+                } else {
+                    Vector::new(0.0, 0.0, -1.0)
+                };
+                (point::ORIGIN, point::ORIGIN + direction)
+            }
+        };
 
         let transformation = self
             .transform
             .inverse()
             .expect("Illegal Camera Transformation!");
 
-        // using the camera matrix, transform canvas point and the origin.
-        let pixel = transformation * Point::new(world_x, world_y, -1.0);
-        let origin = transformation * Point::new(0.0, 0.0, 0.0);
+        // using the camera matrix, transform both points.
+        let origin = transformation * origin;
+        let target = transformation * target;
+
+        let direction = (target - origin).normalize();
+
+        Some(Ray::new(origin, direction))
+    }
+
+    /// Sets `focal_distance` so the object seen at pixel (`x`, `y`) is in focus.
+    /// Returns the new focal distance, `None` (unchanged) if the pixel sees nothing
+    pub fn focus_on(&mut self, world: &World, x: usize, y: usize) -> Option<f64> {
+        let ray = self.ray_for_pixel(x, y)?;
+        let distance = world.intersect(ray).hit()?.intersects_at;
 
-        let direction = (pixel - origin).normalize();
+        // the focal plane is measured along the view direction, not along the ray
+        let direction = self.transform * ray.direction;
+        self.focal_distance = distance * -direction.z;
+        Some(self.focal_distance)
+    }
 
-        Ray::new(origin, direction)
+    /// color seen through the canvas at (`x`, `y`), black outside of the projection
+    fn trace(
+        &self,
+        world: &World,
+        integrator: &dyn Integrator,
+        x: f64,
+        y: f64,
+        rng: &mut Pcg,
+    ) -> Color {
+        match self.ray_for_sample(x, y, rng) {
+            Some(ray) => integrators::trace_with(integrator, world, ray, rng),
+            None => color::BLACK,
+        }
     }
 
-    /// same as `ray_for_pixel` but converts `i` to `(x,y)` and then calls `ray_for_pixel`
-    fn ray_for_pixel_i(&self, i: usize) -> Ray {
-        let y = i / self.hsize;
-        let x = i % self.hsize;
-        self.ray_for_pixel(x, y)
+    /// color of pixel (`x`, `y`), `samples` rays are spread over the filter's
+    /// radius and averaged using the filter's weights
+    fn color_at_pixel(
+        &self,
+        world: &World,
+        integrator: &dyn Integrator,
+        x: usize,
+        y: usize,
+    ) -> Color {
+        self.filter_pixel(x, y, &world.settings, 1, |x, y, rng| {
+            vec![self.trace(world, integrator, x, y, rng)]
+        })[0]
+    }
+
+    /// filter weighted average of `sample` at the pixel's `settings.samples` positions,
+    /// `sample` returns `buffers` colors for every position (in canvas coordinates)
+    fn filter_pixel(
+        &self,
+        x: usize,
+        y: usize,
+        settings: &RenderSettings,
+        buffers: usize,
+        mut sample: impl FnMut(f64, f64, &mut Pcg) -> Vec<Color>,
+    ) -> Vec<Color> {
+        // one stream per pixel, same image for the same seed
+        let mut rng = Pcg::for_pixel(settings.seed, x, y, self.hsize);
+        let samples = settings.samples;
+        let radius = self.filter.radius();
+
+        let mut weighted = vec![color::BLACK; buffers];
+        let mut total_weight = 0.0;
+        let mut absolute_weight = 0.0;
+        // plain average and range of the samples, for when the weights cancel out
+        let mut sums = vec![color::BLACK; buffers];
+        let mut ranges: Vec<Option<(Color, Color)>> = vec![None; buffers];
+        let mut count = 0;
+
+        for (u, v) in self.sample_pattern.positions(samples, &mut rng) {
+            // offset from the pixel's center
+            let dx = (u - 0.5) * 2.0 * radius;
+            let dy = (v - 0.5) * 2.0 * radius;
+            let weight = self.filter.weight(dx, dy);
+
+            let samples = sample(x as f64 + 0.5 + dx, y as f64 + 0.5 + dy, &mut rng);
+            for (b, sample) in samples.into_iter().enumerate() {
+                weighted[b] = weighted[b] + sample * weight;
+                sums[b] = sums[b] + sample;
+                ranges[b] = Some(match ranges[b] {
+                    Some((low, high)) => (low.min(sample), high.max(sample)),
+                    None => (sample, sample),
+                });
+            }
+            total_weight += weight;
+            absolute_weight += weight.abs();
+            count += 1;
+        }
+
+        // negative lobes (`Filter::Mitchell`) can cancel the positive ones with few samples,
+        // dividing by what is left would blow the color up (or flip its sign)
+        if total_weight <= absolute_weight / 4.0 {
+            return sums
+                .iter()
+                .map(|&c| c * (1.0 / count.max(1) as f64))
+                .collect();
+        }
+        weighted
+            .iter()
+            .zip(ranges)
+            .map(|(&c, range)| {
+                let c = c * (1.0 / total_weight);
+                // no ringing beyond the samples
+                range.map_or(c, |(low, high)| c.max(low).min(high))
+            })
+            .collect()
     }
 
     pub fn render(&self, world: World) -> Canvas {
-        let mut canvas = Canvas::new(self.hsize as usize, self.vsize as usize);
-        canvas
-            .grid
-            .iter_mut()
-            // .par_iter_mut()
+        self.render_with_sample_counts(world).0
+    }
+
+    /// same as `render`, with any `Integrator` instead of `RenderSettings::integrator`
+    pub fn render_with_integrator(&self, world: World, integrator: &dyn Integrator) -> Canvas {
+        self.render_region(&world, integrator, Crop::full(self.hsize, self.vsize))
+            .0
+    }
+
+    /// same as `render`, but also returns how many rays were traced for each pixel
+    pub fn render_with_sample_counts(&self, world: World) -> (Canvas, SampleCounts) {
+        let integrator = world.settings.integrator;
+        self.render_region(&world, &integrator, Crop::full(self.hsize, self.vsize))
+    }
+
+    /// Renders every pass in `aovs` in one go, sharing the rays and shading.
+    /// Uses `RenderSettings::samples`, `sample_pattern` and `filter`, `adaptive` is ignored
+    pub fn render_aovs(&self, world: World, aovs: &[Aov]) -> RenderPasses {
+        let mut passes: Vec<(Aov, Canvas)> = aovs
+            .iter()
+            .map(|&aov| (aov, Canvas::new(self.hsize, self.vsize)))
+            .collect();
+
+        // every pass of every pixel
+        let mut pixels = vec![vec![]; self.hsize * self.vsize];
+        fill_rows(&mut pixels, self.hsize, world.settings.threads, |i| {
+            let (x, y) = (i % self.hsize, i / self.hsize);
+            self.filter_pixel(x, y, &world.settings, aovs.len(), |x, y, rng| {
+                match self.ray_for_sample(x, y, rng) {
+                    Some(ray) => Aov::sample(aovs, &world, ray, rng),
+                    None => vec![color::BLACK; aovs.len()],
+                }
+            })
+        });
+        pixels.into_iter().enumerate().for_each(|(i, colors)| {
+            passes
+                .iter_mut()
+                .zip(colors)
+                .for_each(|((_, canvas), color)| canvas.write_i(i, color));
+        });
+
+        RenderPasses { passes }
+    }
+
+    /// Renders only the part of the image inside `crop`,
+    /// into a `Canvas` the size of the crop
+    pub fn render_crop(&self, world: World, crop: Crop) -> Canvas {
+        self.render_region(&world, &world.settings.integrator, crop)
+            .0
+    }
+
+    /// Renders only the part of the image inside `crop`, into the same pixels
+    /// of `canvas` (a previous render by this camera). Other pixels are untouched,
+    /// so a patch can be re-rendered in place
+    pub fn render_crop_into(&self, world: World, crop: Crop, canvas: &mut Canvas) {
+        assert!(
+            canvas.width == self.hsize && canvas.height == self.vsize,
+            "Canvas doesn't match the Camera's size!"
+        );
+        let (x0, y0, _, _) = crop.pixels(self.hsize, self.vsize);
+        let (patch, _) = self.render_region(&world, &world.settings.integrator, crop);
+        paste(canvas, x0, y0, &patch);
+    }
+
+    /// Renders the image in tiles of `TILE_SIZE` pixels, row by row, and stops
+    /// between tiles when `control` is cancelled or runs out of time.
+    /// Adaptive sampling only compares pixels inside the same tile
+    pub fn render_with_control(&self, world: World, control: &RenderControl) -> PartialRender {
+        let tiles_x = self.hsize.div_ceil(TILE_SIZE);
+        let tiles_y = self.vsize.div_ceil(TILE_SIZE);
+        let mut canvas = Canvas::new(self.hsize, self.vsize);
+        let mut completed = vec![false; tiles_x * tiles_y];
+        // the budget starts now, not when `control` was made
+        let started = Instant::now();
+
+        for (tile, done) in completed.iter_mut().enumerate() {
+            if control.should_stop(started) {
+                break;
+            }
+            let crop = Crop::Pixels {
+                x: (tile % tiles_x) * TILE_SIZE,
+                y: (tile / tiles_x) * TILE_SIZE,
+                width: TILE_SIZE,
+                height: TILE_SIZE,
+            };
+            let (x0, y0, _, _) = crop.pixels(self.hsize, self.vsize);
+            let (patch, _) = self.render_region(&world, &world.settings.integrator, crop);
+            paste(&mut canvas, x0, y0, &patch);
+            *done = true;
+        }
+
+        PartialRender {
+            canvas,
+            tile_size: TILE_SIZE,
+            tiles_x,
+            tiles_y,
+            completed,
+        }
+    }
+
+    /// renders the pixels inside `crop` into a `Canvas` the size of the crop
+    fn render_region(
+        &self,
+        world: &World,
+        integrator: &dyn Integrator,
+        crop: Crop,
+    ) -> (Canvas, SampleCounts) {
+        if let Some(adaptive) = self.adaptive {
+            return self.render_adaptive(world, integrator, adaptive, crop);
+        }
+
+        let (x0, y0, width, height) = crop.pixels(self.hsize, self.vsize);
+        let mut canvas = Canvas::new(width, height);
+
+        fill_rows(&mut canvas.grid, width, world.settings.threads, |i| {
+            let y = y0 + i / width;
+            let x = x0 + i % width;
+            self.color_at_pixel(world, integrator, x, y)
+        });
+
+        let counts = SampleCounts {
+            width,
+            height,
+            counts: vec![self.sample_pattern.count(world.settings.samples); width * height],
+        };
+        // canvas
+        (canvas, counts)
+    }
+
+    /// Samples the corners and center of every pixel, then refines the pixels
+    /// whose samples, or whose neighbours, differ by more than the threshold.
+    /// Pixels on the edge of `crop` only compare to neighbours inside it
+    fn render_adaptive(
+        &self,
+        world: &World,
+        integrator: &dyn Integrator,
+        adaptive: Adaptive,
+        crop: Crop,
+    ) -> (Canvas, SampleCounts) {
+        let (x0, y0, width, height) = crop.pixels(self.hsize, self.vsize);
+        let seed = world.settings.seed;
+        // lens samples, one stream per pixel (and per corner) of the whole image,
+        // so tiles and crops don't repeat the same noise
+        let pixel_rng = |x: usize, y: usize| Pcg::for_pixel(seed, x, y, self.hsize);
+        let corner_rng = |x: usize, y: usize| Pcg::for_pixel(!seed, x, y, self.hsize + 1);
+        let trace = |x: f64, y: f64, rng: &mut Pcg| self.trace(world, integrator, x, y, rng);
+
+        let threads = world.settings.threads;
+
+        // corners are shared by neighbouring pixels, trace them once
+        let mut corners = vec![color::BLACK; (width + 1) * (height + 1)];
+        fill_rows(&mut corners, width + 1, threads, |i| {
+            let x = x0 + i % (width + 1);
+            let y = y0 + i / (width + 1);
+            trace(x as f64, y as f64, &mut corner_rng(x, y))
+        });
+        let corner = |x: usize, y: usize| corners[y * (width + 1) + x];
+
+        // coarse pass, corners and center of each pixel
+        let mut coarse = vec![([color::BLACK; 4], color::BLACK); width * height];
+        fill_rows(&mut coarse, width, threads, |i| {
+            let (x, y) = (i % width, i / width);
+            let quad = [
+                corner(x, y),
+                corner(x + 1, y),
+                corner(x, y + 1),
+                corner(x + 1, y + 1),
+            ];
+            let (x, y) = (x0 + x, y0 + y);
+            let center = trace(x as f64 + 0.5, y as f64 + 0.5, &mut pixel_rng(x, y));
+            (quad, center)
+        });
+        let estimate = |i: usize| average(&coarse[i].0, coarse[i].1);
+
+        let mut refined = vec![(color::BLACK, 0); width * height];
+        fill_rows(&mut refined, width, threads, |i| {
+            let (x, y) = (i % width, i / width);
+            let own = estimate(i);
+
+            // pixels that differ from their neighbours are refined,
+            // even if their own samples agree (thin features between samples)
+            let mut neighbours = vec![own];
+            if x > 0 {
+                neighbours.push(estimate(i - 1));
+            }
+            if x + 1 < width {
+                neighbours.push(estimate(i + 1));
+            }
+            if y > 0 {
+                neighbours.push(estimate(i - width));
+            }
+            if y + 1 < height {
+                neighbours.push(estimate(i + width));
+            }
+            let force = adaptive.differ(&neighbours);
+
+            let (quad, center) = coarse[i];
+            let quadrant = Quadrant {
+                x: (x0 + x) as f64,
+                y: (y0 + y) as f64,
+                size: 1.0,
+            };
+            // independent of the center's sample
+            let mut rng = pixel_rng(x0 + x, y0 + y).split();
+            let mut trace = |x: f64, y: f64| trace(x, y, &mut rng);
+            let (color, rays) = refine(&mut trace, &adaptive, quadrant, quad, center, 0, force);
+            // four corners and center
+            (color, 5 + rays)
+        });
+
+        let mut canvas = Canvas::new(width, height);
+        refined
+            .iter()
             .enumerate()
-            .for_each(|(i, color)| {
-                let ray = self.ray_for_pixel_i(i);
-                *color = world.color_at(ray, constants::MAX_REFLECTION_RECRUSTION);
+            .for_each(|(i, &(color, _))| canvas.write_i(i, color));
+        let counts = SampleCounts {
+            width,
+            height,
+            counts: refined.iter().map(|&(_, rays)| rays).collect(),
+        };
+        (canvas, counts)
+    }
+}
+
+/// width and height of the tiles traced by `Camera::render_with_control`
+pub const TILE_SIZE: usize = 16;
+
+/// Sets every value of `values` (rows `width` long) to `value(index)`,
+/// split between `threads`, every thread gets a band of rows
+fn fill_rows<T: Send>(
+    values: &mut [T],
+    width: usize,
+    threads: usize,
+    value: impl Fn(usize) -> T + Sync,
+) {
+    let height = values.len().checked_div(width).unwrap_or(0);
+    let band = height.div_ceil(threads.max(1)).max(1) * width;
+    if band == 0 {
+        return;
+    }
+    let value = &value;
+    std::thread::scope(|scope| {
+        values.chunks_mut(band).enumerate().for_each(|(n, chunk)| {
+            scope.spawn(move || {
+                chunk
+                    .iter_mut()
+                    .enumerate()
+                    .for_each(|(i, v)| *v = value(n * band + i));
             });
-        // canvas
-        canvas
+        });
+    });
+}
+
+/// copies `patch` into `canvas`, with its top left corner at (`x`, `y`)
+fn paste(canvas: &mut Canvas, x: usize, y: usize, patch: &Canvas) {
+    patch.grid.iter().enumerate().for_each(|(i, color)| {
+        canvas.write(x + i % patch.width, y + i / patch.width, *color);
+    });
+}
+
+/// Part of the image to render
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Crop {
+    /// top left corner at (`x`, `y`), `width` by `height` pixels
+    Pixels {
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    },
+    /// edges as fractions of the image, (`0.0`, `0.0`) is the top left corner
+    /// and (`1.0`, `1.0`) the bottom right. Partially covered pixels are included
+    Normalized {
+        left: f64,
+        top: f64,
+        right: f64,
+        bottom: f64,
+    },
+}
+
+impl Crop {
+    /// the whole `hsize` by `vsize` image
+    pub fn full(hsize: usize, vsize: usize) -> Self {
+        Crop::Pixels {
+            x: 0,
+            y: 0,
+            width: hsize,
+            height: vsize,
+        }
+    }
+
+    /// Returns `(x, y, width, height)` in pixels, clamped to a `hsize` by `vsize` image
+    pub fn pixels(&self, hsize: usize, vsize: usize) -> (usize, usize, usize, usize) {
+        let (x0, y0, x1, y1) = match *self {
+            Crop::Pixels {
+                x,
+                y,
+                width,
+                height,
+            } => (x, y, x + width, y + height),
+            Crop::Normalized {
+                left,
+                top,
+                right,
+                bottom,
+            } => {
+                let to_pixel = |v: f64, size: usize| v.clamp(0.0, 1.0) * size as f64;
+                (
+                    to_pixel(left, hsize).floor() as usize,
+                    to_pixel(top, vsize).floor() as usize,
+                    to_pixel(right, hsize).ceil() as usize,
+                    to_pixel(bottom, vsize).ceil() as usize,
+                )
+            }
+        };
+
+        let (x0, y0) = (x0.min(hsize), y0.min(vsize));
+        let (x1, y1) = (x1.clamp(x0, hsize), y1.clamp(y0, vsize));
+        (x0, y0, x1 - x0, y1 - y0)
     }
 }
 
+/// square area of the canvas, in pixels
+#[derive(Debug, Clone, Copy)]
+struct Quadrant {
+    x: f64,
+    y: f64,
+    size: f64,
+}
+
+/// Splits `quadrant` into four while its samples differ, returns its color
+/// and the number of extra rays traced.
+/// `corners` are top left, top right, bottom left and bottom right.
+fn refine(
+    trace: &mut dyn FnMut(f64, f64) -> Color,
+    adaptive: &Adaptive,
+    quadrant: Quadrant,
+    corners: [Color; 4],
+    center: Color,
+    depth: usize,
+    force: bool,
+) -> (Color, usize) {
+    let [top_left, top_right, bottom_left, bottom_right] = corners;
+    let flat = !adaptive.differ(&[top_left, top_right, bottom_left, bottom_right, center]);
+
+    if depth >= adaptive.max_depth || (flat && !force) {
+        return (average(&corners, center), 0);
+    }
+
+    let Quadrant { x, y, size } = quadrant;
+    let half = size / 2.0;
+
+    // midpoints of the edges
+    let top = trace(x + half, y);
+    let left = trace(x, y + half);
+    let right = trace(x + size, y + half);
+    let bottom = trace(x + half, y + size);
+
+    let children = [
+        (x, y, [top_left, top, left, center]),
+        (x + half, y, [top, top_right, center, right]),
+        (x, y + half, [left, center, bottom_left, bottom]),
+        (x + half, y + half, [center, right, bottom, bottom_right]),
+    ];
+
+    let mut color = color::BLACK;
+    let mut rays = 4;
+    for (x, y, corners) in children.iter().copied() {
+        let quadrant = Quadrant { x, y, size: half };
+        let center = trace(x + half / 2.0, y + half / 2.0);
+        let (c, r) = refine(trace, adaptive, quadrant, corners, center, depth + 1, false);
+        color = color + c * 0.25;
+        rays += 1 + r;
+    }
+    (color, rays)
+}
+
+/// mean of the four corners and the center of a quadrant
+fn average(corners: &[Color; 4], center: Color) -> Color {
+    corners.iter().fold(center, |sum, &c| sum + c) * (1.0 / 5.0)
+}
+
 // ================================= TESTS =======================================
 
 #[cfg(test)]
 mod tests {
-    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, SQRT_2};
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI, SQRT_2};
 
     use crate::{
         math::{point::Point, vector::Vector},
@@ -129,7 +767,7 @@ mod tests {
     #[test]
     fn ray_through_center_of_canvas() {
         let camera = Camera::new(201, 101, FRAC_PI_2);
-        let ray = camera.ray_for_pixel(100, 50);
+        let ray = camera.ray_for_pixel(100, 50).unwrap();
 
         assert_eq!(ray.origin, Point::new(0.0, 0.0, 0.0));
         assert_eq!(ray.direction, Vector::new(0.0, 0.0, -1.0));
@@ -138,7 +776,7 @@ mod tests {
     #[test]
     fn ray_through_corner_of_canvas() {
         let camera = Camera::new(201, 101, FRAC_PI_2);
-        let ray = camera.ray_for_pixel(0, 0);
+        let ray = camera.ray_for_pixel(0, 0).unwrap();
 
         assert_eq!(ray.origin, Point::new(0.0, 0.0, 0.0));
         Testing::assert_nearly_eq(ray.direction, Vector::new(0.66519, 0.33259, -0.66851))
@@ -149,9 +787,66 @@ mod tests {
         let mut camera = Camera::new(201, 101, FRAC_PI_2);
         camera.transform =
             Transformation::rotate_y(FRAC_PI_4) * Transformation::translation(0.0, -2.0, 5.0);
-        let ray = camera.ray_for_pixel(100, 50);
+        let ray = camera.ray_for_pixel(100, 50).unwrap();
 
         assert_eq!(ray.origin, Point::new(0.0, 2.0, -5.0));
         Testing::assert_nearly_eq(ray.direction, Vector::new(SQRT_2 / 2.0, 0.0, -SQRT_2 / 2.0))
     }
+
+    #[test]
+    fn rays_through_the_lens_meet_on_the_focal_plane() {
+        let mut camera = Camera::new(201, 101, FRAC_PI_2);
+        camera.focal_distance = 4.0;
+
+        let center = camera.ray_for_film(30.5, 20.5).unwrap();
+        let edge = camera.ray_through_lens(30.5, 20.5, 0.25, -0.1).unwrap();
+
+        // both rays reach z = -4 at the same point
+        let center = center.position(-4.0 / center.direction.z);
+        let edge = edge.position(-4.0 / edge.direction.z);
+        Testing::assert_nearly_eq(center, edge);
+        assert_eq!(edge.z, -4.0);
+    }
+
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let mut camera = Camera::new(200, 100, FRAC_PI_2);
+        camera.projection = Projection::Orthographic { width: 10.0 };
+
+        let center = camera.ray_for_film(100.0, 50.0).unwrap();
+        let corner = camera.ray_for_film(0.0, 0.0).unwrap();
+
+        assert_eq!(center.origin, Point::new(0.0, 0.0, 0.0));
+        assert_eq!(center.direction, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(corner.origin, Point::new(5.0, 2.5, 0.0));
+        assert_eq!(corner.direction, Vector::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn equirectangular_rays_cover_the_sphere() {
+        let mut camera = Camera::new(200, 100, FRAC_PI_2);
+        camera.projection = Projection::Equirectangular;
+
+        let direction = |x, y| camera.ray_for_film(x, y).unwrap().direction;
+
+        Testing::assert_nearly_eq(direction(100.0, 50.0), Vector::new(0.0, 0.0, -1.0));
+        Testing::assert_nearly_eq(direction(50.0, 50.0), Vector::new(1.0, 0.0, 0.0));
+        Testing::assert_nearly_eq(direction(0.0, 50.0), Vector::new(0.0, 0.0, 1.0));
+        Testing::assert_nearly_eq(direction(100.0, 0.0), Vector::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn fisheye_rays_inside_image_circle() {
+        let mut camera = Camera::new(100, 100, PI);
+        camera.projection = Projection::Fisheye;
+
+        let center = camera.ray_for_film(50.0, 50.0).unwrap();
+        Testing::assert_nearly_eq(center.direction, Vector::new(0.0, 0.0, -1.0));
+
+        // 180 degrees across, the edge of the circle looks sideways
+        let edge = camera.ray_for_film(50.0, 0.0).unwrap();
+        Testing::assert_nearly_eq(edge.direction, Vector::new(0.0, 1.0, 0.0));
+
+        assert!(camera.ray_for_pixel(0, 0).is_none());
+    }
 }
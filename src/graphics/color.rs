@@ -50,6 +50,24 @@ impl Color {
         Color { red, green, blue }
     }
 
+    /// Smaller of each channel
+    pub fn min(&self, other: Color) -> Self {
+        Self::new(
+            self.red.min(other.red),
+            self.green.min(other.green),
+            self.blue.min(other.blue),
+        )
+    }
+
+    /// Larger of each channel
+    pub fn max(&self, other: Color) -> Self {
+        Self::new(
+            self.red.max(other.red),
+            self.green.max(other.green),
+            self.blue.max(other.blue),
+        )
+    }
+
     /// Restricts all colors in `Color` to a certain range
     pub fn scale(&self, min: f64, max: f64) -> Self {
         Self {
@@ -0,0 +1,82 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use super::canvas::Canvas;
+
+/// Handle to stop a render in progress, cloned handles share the same state,
+/// so one can be moved to another thread and cancel the render from there
+#[derive(Debug, Clone, Default)]
+pub struct RenderControl {
+    cancelled: Arc<AtomicBool>,
+    /// wall-clock time the render may take, counted from when it starts
+    pub budget: Option<Duration>,
+}
+
+impl RenderControl {
+    /// Returns a new handle without a time budget
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a new handle, the render stops `budget` (wall-clock) after it started
+    pub fn with_budget(budget: Duration) -> Self {
+        Self {
+            budget: Some(budget),
+            ..Self::default()
+        }
+    }
+
+    /// Stops the render after the tile currently being traced
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// true after `cancel` was called on this handle or any of its clones
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// true when the render was cancelled or ran out of time, `started` is when it began
+    pub fn should_stop(&self, started: Instant) -> bool {
+        self.is_cancelled() || self.budget.is_some_and(|b| started.elapsed() >= b)
+    }
+}
+
+/// Result of a render that may have been stopped early.
+/// The image is traced in `tile_size` squares, row by row, unfinished tiles are black
+#[derive(Debug, Clone)]
+pub struct PartialRender {
+    pub canvas: Canvas,
+    /// width and height of a tile in pixels (tiles on the right and bottom edges may be smaller)
+    pub tile_size: usize,
+    /// tiles per row
+    pub tiles_x: usize,
+    /// tiles per column
+    pub tiles_y: usize,
+    /// one entry per tile, row major
+    pub completed: Vec<bool>,
+}
+
+impl PartialRender {
+    /// true when every tile was traced
+    pub fn is_complete(&self) -> bool {
+        self.completed.iter().all(|&done| done)
+    }
+
+    /// true when the tile holding pixel (`x`, `y`) was traced
+    pub fn is_pixel_completed(&self, x: usize, y: usize) -> bool {
+        self.completed[(y / self.tile_size) * self.tiles_x + x / self.tile_size]
+    }
+
+    /// Number of traced pixels
+    pub fn completed_pixels(&self) -> usize {
+        (0..self.canvas.width * self.canvas.height)
+            .filter(|i| self.is_pixel_completed(i % self.canvas.width, i / self.canvas.width))
+            .count()
+    }
+}
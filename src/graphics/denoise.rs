@@ -0,0 +1,135 @@
+use super::{
+    aov::{Aov, RenderPasses},
+    canvas::Canvas,
+    color::Color,
+};
+
+/// Edge aware joint bilateral filter, for noisy renders with few samples per pixel.
+/// Every pixel becomes a weighted average of its neighbours, neighbours weigh less
+/// the further away they are and the more they differ in color, or in the guide
+/// buffers (albedo, normal and depth), so edges and texture stay sharp.
+/// Each `sigma` is a strength, larger blurs more across differences,
+/// it is never less than `MIN_SIGMA`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Denoiser {
+    /// neighbours up to `radius` pixels away (in each direction) are averaged, defaults to `3`
+    pub radius: usize,
+    /// distance falloff in pixels, defaults to `2.0`
+    pub sigma_spatial: f64,
+    /// how different (noisy) colors can be and still be averaged, defaults to `0.3`
+    pub sigma_color: f64,
+    /// difference in surface color, keeps texture edges. Defaults to `0.1`
+    pub sigma_albedo: f64,
+    /// difference in normals, keeps geometric creases. Defaults to `0.3`
+    pub sigma_normal: f64,
+    /// difference in depth relative to the pixel's depth,
+    /// keeps object silhouettes. Defaults to `0.1`
+    pub sigma_depth: f64,
+}
+
+/// smallest `sigma` a `Denoiser` uses, `0.0` would divide by zero
+pub const MIN_SIGMA: f64 = 1e-6;
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Self {
+            radius: 3,
+            sigma_spatial: 2.0,
+            sigma_color: 0.3,
+            sigma_albedo: 0.1,
+            sigma_normal: 0.3,
+            sigma_depth: 0.1,
+        }
+    }
+}
+
+/// Noise free buffers the `Denoiser` follows edges in,
+/// same size as the image (others are ignored). See `Aov::Albedo`, `Aov::Normal` and `Aov::Depth`
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Guides<'a> {
+    pub albedo: Option<&'a Canvas>,
+    pub normal: Option<&'a Canvas>,
+    pub depth: Option<&'a Canvas>,
+}
+
+impl<'a> Guides<'a> {
+    /// the albedo, normal and depth passes, if they were rendered
+    pub fn from_passes(passes: &'a RenderPasses) -> Self {
+        Self {
+            albedo: passes.get(Aov::Albedo),
+            normal: passes.get(Aov::Normal),
+            depth: passes.get(Aov::Depth),
+        }
+    }
+}
+
+impl Denoiser {
+    /// Returns a denoised copy of `image`. Guides of another size are ignored
+    pub fn denoise(&self, image: &Canvas, guides: &Guides) -> Canvas {
+        let (width, height) = (image.width, image.height);
+        let radius = self.radius as isize;
+        let mut output = Canvas::new(width, height);
+
+        let fits = |guide: &&Canvas| guide.width == width && guide.height == height;
+        let (albedo, normal, depth) = (
+            guides.albedo.filter(fits),
+            guides.normal.filter(fits),
+            guides.depth.filter(fits),
+        );
+        let sigma = |sigma: f64| sigma.max(MIN_SIGMA);
+        let sigma_spatial = sigma(self.sigma_spatial);
+        let sigma_color = sigma(self.sigma_color);
+        let sigma_albedo = sigma(self.sigma_albedo);
+        let sigma_normal = sigma(self.sigma_normal);
+        let sigma_depth = sigma(self.sigma_depth);
+
+        (0..width * height).for_each(|i| {
+            let (x, y) = ((i % width) as isize, (i / width) as isize);
+
+            let mut sum = Color::new(0.0, 0.0, 0.0);
+            let mut total_weight = 0.0;
+
+            for ny in (y - radius).max(0)..(y + radius + 1).min(height as isize) {
+                for nx in (x - radius).max(0)..(x + radius + 1).min(width as isize) {
+                    let j = ny as usize * width + nx as usize;
+
+                    let distance2 = ((nx - x).pow(2) + (ny - y).pow(2)) as f64;
+                    let mut exponent = distance2 / (2.0 * sigma_spatial.powi(2));
+                    exponent += distance(image.grid[i], image.grid[j]) / sigma_color.powi(2);
+
+                    if let Some(albedo) = albedo {
+                        exponent += distance(albedo.grid[i], albedo.grid[j]) / sigma_albedo.powi(2);
+                    }
+                    if let Some(normal) = normal {
+                        exponent += distance(normal.grid[i], normal.grid[j]) / sigma_normal.powi(2);
+                    }
+                    if let Some(depth) = depth {
+                        let (d0, d1) = (depth.grid[i].red, depth.grid[j].red);
+                        let relative = (d1 - d0) / d0.abs().max(1e-6);
+                        exponent += relative.powi(2) / (2.0 * sigma_depth.powi(2));
+                    }
+
+                    let weight = (-exponent).exp();
+                    sum = sum + image.grid[j] * weight;
+                    total_weight += weight;
+                }
+            }
+            // the pixel itself always has weight 1
+            output.write_i(i, sum * (1.0 / total_weight));
+        });
+        output
+    }
+
+    /// Denoises the `Beauty` pass, guided by whichever of the albedo, normal
+    /// and depth passes were rendered. `None` without a `Beauty` pass
+    pub fn denoise_passes(&self, passes: &RenderPasses) -> Option<Canvas> {
+        let beauty = passes.get(Aov::Beauty)?;
+        Some(self.denoise(beauty, &Guides::from_passes(passes)))
+    }
+}
+
+/// half the squared distance between two colors
+fn distance(a: Color, b: Color) -> f64 {
+    let d = a - b;
+    (d.red.powi(2) + d.green.powi(2) + d.blue.powi(2)) / 2.0
+}
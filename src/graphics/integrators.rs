@@ -0,0 +1,265 @@
+use crate::{
+    math::{
+        ray::{Ray, RayKind},
+        sampling::{self, Pcg},
+        vector::Vector,
+    },
+    objects::{intersections::PreComputed, world::World},
+};
+
+use super::{
+    color::{self, Color},
+    materials::Material,
+    spectrum::{self, MAX_WAVELENGTH, MIN_WAVELENGTH},
+};
+
+/// Finds the light arriving at the camera along a ray,
+/// `Camera` traces one ray per sample and averages the results.
+/// `Integrators` (in `RenderSettings::integrator`) picks one of the built-in ones,
+/// others are rendered with `Camera::render_with_integrator`.
+/// Shared between the render threads, hence `Sync`
+pub trait Integrator: Sync {
+    /// `Color` seen along `ray`, `rng` is there for stochastic integrators
+    fn li(&self, world: &World, ray: Ray, rng: &mut Pcg) -> Color;
+}
+
+/// Color along `ray` with `world.settings.integrator`, see `trace_with`
+pub fn trace(world: &World, ray: Ray, rng: &mut Pcg) -> Color {
+    trace_with(&world.settings.integrator, world, ray, rng)
+}
+
+/// Color along `ray` with `integrator`. In spectral mode
+/// (`RenderSettings::spectral`) the ray carries one random wavelength,
+/// and the result is weighted by that wavelength's RGB
+pub fn trace_with(integrator: &dyn Integrator, world: &World, ray: Ray, rng: &mut Pcg) -> Color {
+    if !world.settings.spectral {
+        return integrator.li(world, ray, rng);
+    }
+
+    let wavelength = MIN_WAVELENGTH + rng.next_f64() * (MAX_WAVELENGTH - MIN_WAVELENGTH);
+    let ray = Ray {
+        wavelength: Some(wavelength),
+        ..ray
+    };
+    integrator.li(world, ray, rng) * spectrum::wavelength_to_rgb(wavelength)
+}
+
+/// Selects the integrator used by `Camera::render`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Integrators {
+    Whitted(Whitted),
+    PathTracer(PathTracer),
+    Debug(DebugView),
+}
+
+impl Default for Integrators {
+    fn default() -> Self {
+        Integrators::Whitted(Whitted)
+    }
+}
+
+impl Integrator for Integrators {
+    fn li(&self, world: &World, ray: Ray, rng: &mut Pcg) -> Color {
+        match self {
+            Integrators::Whitted(whitted) => whitted.li(world, ray, rng),
+            Integrators::PathTracer(path_tracer) => path_tracer.li(world, ray, rng),
+            Integrators::Debug(view) => view.li(world, ray, rng),
+        }
+    }
+}
+
+/// Classic recursive ray tracing: direct light with hard shadows,
+/// plus perfect mirror reflection and refraction, up to `RenderSettings::max_depth` bounces.
+/// See `World::color_at`
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Whitted;
+
+impl Integrator for Whitted {
+    fn li(&self, world: &World, ray: Ray, _rng: &mut Pcg) -> Color {
+        world.color_at(ray, world.settings.max_depth)
+    }
+}
+
+/// Monte Carlo path tracing, for global illumination (color bleeding, soft indirect light).
+/// Every hit adds the direct light (next-event estimation towards `World::lights`),
+/// then the path continues along one lobe, picked by the `Material`:
+/// `diffuse` (cosine weighted bounce, tinted by `color`), `reflective` or `transparency`.
+/// `ambient` is ignored, the indirect light replaces it.
+/// Paths are at most `RenderSettings::max_depth` bounces long.
+/// Participating media dim the path and add the light they scatter towards it (`World::participate`).
+/// Noisy, raise `RenderSettings::samples` to get a smoother image
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PathTracer {
+    /// paths shorter than this are never terminated by Russian roulette. Defaults to `3`
+    pub roulette_depth: usize,
+}
+
+impl Default for PathTracer {
+    fn default() -> Self {
+        Self { roulette_depth: 3 }
+    }
+}
+
+impl Integrator for PathTracer {
+    fn li(&self, world: &World, ray: Ray, rng: &mut Pcg) -> Color {
+        let mut radiance = color::BLACK;
+        // how much the light found further along the path still counts
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        let mut ray = ray;
+
+        for depth in 0..world.settings.max_depth.max(0) as usize {
+            let xs = world.intersect(ray);
+            let hit = xs.hit();
+
+            // fog and smoke in front of the hit, single scattering only
+            let t_max = hit.map_or(f64::INFINITY, |hit| hit.intersects_at);
+            let (transmittance, scattered) = world.participate(ray, t_max);
+            radiance = radiance + throughput * scattered;
+            throughput = throughput * transmittance;
+
+            let comps = match hit.and_then(|hit| hit.prepare_computations(ray, Some(&xs))) {
+                Some(comps) => comps,
+                None => {
+                    radiance = radiance + throughput * world.settings.background;
+                    break;
+                }
+            };
+            let material = comps.object.material;
+            throughput = throughput * comps.transmittance();
+
+            radiance = radiance + throughput * direct_light(world, &comps);
+
+            // pick a lobe, proportional to its weight
+            let (reflective, transparent) = comps.lobe_weights();
+            let diffuse = material.diffuse;
+            let total = diffuse + reflective + transparent;
+            if total <= 0.0 {
+                break;
+            }
+
+            let pick = rng.next_f64() * total;
+            let over_point = comps.point + comps.normalv * world.settings.ray_bias;
+            let under_point = comps.point - comps.normalv * world.settings.ray_bias;
+            let white = Color::new(1.0, 1.0, 1.0);
+            let reflection = (comps.reflectv, over_point, white, RayKind::Reflection);
+            let (direction, origin, tint, kind) = if pick < diffuse {
+                let (u, v) = (rng.next_f64(), rng.next_f64());
+                let albedo = material.color_at(&comps.object, comps.point);
+                let direction = cosine_around(comps.normalv, u, v);
+                (direction, over_point, albedo, RayKind::Reflection)
+            } else if pick < diffuse + reflective {
+                reflection
+            } else {
+                match comps.refracted_direction() {
+                    Some(direction) => (direction, under_point, white, RayKind::Refraction),
+                    // total internal reflection
+                    None => reflection,
+                }
+            };
+            // dividing by the chance of the lobe leaves `total`
+            throughput = throughput * tint * total;
+
+            // Russian roulette, dim paths are likely to stop, survivors are brightened
+            if depth + 1 >= self.roulette_depth {
+                let survive = throughput
+                    .red
+                    .max(throughput.green)
+                    .max(throughput.blue)
+                    .clamp(0.05, 1.0);
+                if rng.next_f64() >= survive {
+                    break;
+                }
+                throughput = throughput * (1.0 / survive);
+            }
+
+            ray = comps.secondary_ray(origin, direction, kind);
+        }
+        radiance
+    }
+}
+
+/// Phong diffuse and specular from `World::lights` at the hit, without `ambient`
+fn direct_light(world: &World, comps: &PreComputed) -> Color {
+    let material = Material {
+        ambient: 0.0,
+        ..comps.object.material
+    };
+    world.lights.iter().fold(color::BLACK, |sum, light| {
+        let light_attenuation = world.surface_light(light, comps);
+        sum + material.lighting(
+            comps.object,
+            *light,
+            world.shadow_point(comps),
+            comps.eyev,
+            comps.normalv,
+            light_attenuation,
+        )
+    })
+}
+
+/// cosine weighted direction on the hemisphere around `normal`
+fn cosine_around(normal: Vector, u: f64, v: f64) -> Vector {
+    let (x, y, z) = sampling::cosine_hemisphere(u, v);
+    let (tangent, bitangent) = sampling::basis_around(normal);
+    (tangent * x + bitangent * y + normal * z).normalize()
+}
+
+/// Shows a property of the first surface hit instead of shading it,
+/// for diagnosing scenes. Misses are black
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DebugView {
+    /// world space normal, each axis mapped from `[-1, 1]` to `[0, 1]`
+    Normals,
+    /// distance along the ray, white at the camera fading to black at `max`
+    Depth { max: f64 },
+    /// a random flat color for every object
+    ObjectId,
+    /// texture coordinates, `u` is red and `v` is green
+    Uv,
+    /// cosine between the normal and the direction to the camera,
+    /// white when facing the camera and black at grazing angles
+    FacingRatio,
+}
+
+impl Integrator for DebugView {
+    fn li(&self, world: &World, ray: Ray, _rng: &mut Pcg) -> Color {
+        let xs = world.intersect(ray);
+        let hit = match xs.hit() {
+            Some(hit) => hit,
+            None => return color::BLACK,
+        };
+        let object = hit.object;
+        let point = ray.position(hit.intersects_at);
+
+        match self {
+            DebugView::Normals => match object.normal_at(point) {
+                Some(n) => Color::new((n.x + 1.0) / 2.0, (n.y + 1.0) / 2.0, (n.z + 1.0) / 2.0),
+                None => color::BLACK,
+            },
+            DebugView::Depth { max } => {
+                let distance = hit.intersects_at * ray.direction.magnitude();
+                let v = (1.0 - distance / max).clamp(0.0, 1.0);
+                Color::new(v, v, v)
+            }
+            DebugView::ObjectId => id_color(object.uid),
+            DebugView::Uv => match object.uv_at(point) {
+                Some((u, v)) => Color::new(u, v, 0.0),
+                None => color::BLACK,
+            },
+            DebugView::FacingRatio => match object.normal_at(point) {
+                Some(n) => {
+                    let v = n.dot_product(&-ray.direction.normalize()).abs();
+                    Color::new(v, v, v)
+                }
+                None => color::BLACK,
+            },
+        }
+    }
+}
+
+/// a random flat color for a `Shape`'s `uid`, same `uid` same color
+pub(crate) fn id_color(uid: u128) -> Color {
+    // uid is unique, hash it into a color
+    let mut rng = Pcg::new(uid as u64, (uid >> 64) as u64);
+    Color::new(rng.next_f64(), rng.next_f64(), rng.next_f64())
+}
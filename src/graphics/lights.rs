@@ -1,17 +1,261 @@
-use super::color::Color;
-use crate::math::point::Point;
+use super::{antialiasing::SamplePattern, color::Color};
+use crate::math::{
+    point::Point,
+    sampling::{self, Pcg},
+    vector::Vector,
+};
 
-/// Point of light in 3d space, with no size,
-/// intensisty is defined by `Color`
+/// Source of light, intensisty is defined by `Color`.
+/// A point by default, area lights (see `LightType`) cast soft shadows,
+/// spot lights light a cone and directional lights shine from infinitely far away
 #[derive(PartialEq, Clone, Copy, Debug)]
-pub struct PointLight {
+pub struct Light {
+    /// center of the light, unused by directional lights
     pub position: Point,
     /// color is also intensisty
     pub color: Color,
+    /// brightness multiplier on `color`, defaults to `1.0`
+    pub power: f64,
+    /// how the light dims with distance, defaults to `Falloff::None`
+    pub falloff: Falloff,
+    /// Holds the shape of the light, see `LightType`
+    pub light_type: LightType,
+    /// points on area lights tested for shadows, spread on a jittered grid,
+    /// so rounded up to a perfect square (5 traces 9, see `SamplePattern::count`).
+    /// More gives smoother penumbrae. Other lights always use 1
+    pub samples: usize,
 }
 
-impl PointLight {
+/// Former name of `Light`, from when every light was a point
+pub type PointLight = Light;
+
+/// Attenuation of a `Light` with the distance `d` from it, never more than `MAX_FALLOFF`
+/// (at the light itself `1 / d` would be infinite). Directional lights never fall off
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub enum Falloff {
+    /// same brightness everywhere
+    #[default]
+    None,
+    /// `1 / d`
+    Linear,
+    /// `1 / d^2`, physically correct, `Light::power` has to grow with the scene
+    InverseSquare,
+    /// `1 / (constant + linear * d + quadratic * d^2)`
+    Custom {
+        constant: f64,
+        linear: f64,
+        quadratic: f64,
+    },
+}
+
+/// largest `Falloff::at`, close to a light or with `Custom` coefficients adding up to `0`
+pub const MAX_FALLOFF: f64 = 1e6;
+
+impl Falloff {
+    /// share of the light left at `distance`, at most `MAX_FALLOFF`
+    pub fn at(&self, distance: f64) -> f64 {
+        let denominator = match *self {
+            Falloff::None => 1.0,
+            Falloff::Linear => distance,
+            Falloff::InverseSquare => distance * distance,
+            Falloff::Custom {
+                constant,
+                linear,
+                quadratic,
+            } => constant + linear * distance + quadratic * distance * distance,
+        };
+        1.0 / denominator.max(1.0 / MAX_FALLOFF)
+    }
+}
+
+/// Shape of a `Light`, all centered on `Light::position`
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum LightType {
+    /// no size, sharp shadows
+    Point,
+    /// parallelogram with edges `u` and `v` (the full length of each side)
+    Rectangle { u: Vector, v: Vector },
+    /// flat disk facing `normal`
+    Disk { radius: f64, normal: Vector },
+    /// ball of light
+    Sphere { radius: f64 },
+    /// point light shining along `direction`, full brightness up to `inner_angle`
+    /// from it, fading smoothly to dark at `outer_angle` (both in radians)
+    Spot {
+        direction: Vector,
+        inner_angle: f64,
+        outer_angle: f64,
+    },
+    /// sunlight, parallel rays travelling along `direction` with no falloff,
+    /// shadows are cast the same way everywhere
+    Directional { direction: Vector },
+}
+
+impl Light {
+    /// Point light
     pub fn new(position: Point, color: Color) -> Self {
-        Self { position, color }
+        Self {
+            position,
+            color,
+            power: 1.0,
+            falloff: Falloff::None,
+            light_type: LightType::Point,
+            samples: 1,
+        }
+    }
+
+    /// Rectangular area light, `u` and `v` are its edges
+    pub fn rectangle(position: Point, u: Vector, v: Vector, color: Color, samples: usize) -> Self {
+        Self {
+            light_type: LightType::Rectangle { u, v },
+            samples,
+            ..Self::new(position, color)
+        }
+    }
+
+    /// Round area light facing `normal`
+    pub fn disk(
+        position: Point,
+        radius: f64,
+        normal: Vector,
+        color: Color,
+        samples: usize,
+    ) -> Self {
+        Self {
+            light_type: LightType::Disk {
+                radius,
+                normal: normal.normalize(),
+            },
+            samples,
+            ..Self::new(position, color)
+        }
+    }
+
+    /// Spherical area light
+    pub fn sphere(position: Point, radius: f64, color: Color, samples: usize) -> Self {
+        Self {
+            light_type: LightType::Sphere { radius },
+            samples,
+            ..Self::new(position, color)
+        }
+    }
+
+    /// Spot light at `position` pointing along `direction`, see `LightType::Spot`
+    pub fn spot(
+        position: Point,
+        direction: Vector,
+        inner_angle: f64,
+        outer_angle: f64,
+        color: Color,
+    ) -> Self {
+        Self {
+            light_type: LightType::Spot {
+                direction: direction.normalize(),
+                inner_angle,
+                outer_angle,
+            },
+            ..Self::new(position, color)
+        }
+    }
+
+    /// Sunlight travelling along `direction`
+    pub fn directional(direction: Vector, color: Color) -> Self {
+        Self {
+            light_type: LightType::Directional {
+                direction: direction.normalize(),
+            },
+            ..Self::new(Point::new(0.0, 0.0, 0.0), color)
+        }
+    }
+
+    /// true for lights with a size, see `sample_points`
+    pub fn is_area(&self) -> bool {
+        matches!(
+            self.light_type,
+            LightType::Rectangle { .. } | LightType::Disk { .. } | LightType::Sphere { .. }
+        )
+    }
+
+    /// normalized direction from `point` towards the (center of the) light
+    pub fn direction_from(&self, point: Point) -> Vector {
+        match self.light_type {
+            LightType::Directional { direction } => -direction,
+            _ => (self.position - point).normalize(),
+        }
+    }
+
+    /// distance from `point` to the (center of the) light, infinite for directional lights
+    pub fn distance_from(&self, point: Point) -> f64 {
+        match self.light_type {
+            LightType::Directional { .. } => f64::INFINITY,
+            _ => (self.position - point).magnitude(),
+        }
+    }
+
+    /// color of the light arriving at `point`, ignoring shadows.
+    /// `color` times `power`, dimmed by the `falloff` and outside the inner cone of spot lights
+    pub fn intensity_at(&self, point: Point) -> Color {
+        let cone = match self.light_type {
+            LightType::Spot {
+                direction,
+                inner_angle,
+                outer_angle,
+            } => {
+                let offset = point - self.position;
+                let cos_angle = offset.normalize().dot_product(&direction);
+                let (cos_inner, cos_outer) = (inner_angle.cos(), outer_angle.cos());
+                // no direction at the apex, it is inside the cone
+                if offset.magnitude() == 0.0 || cos_angle >= cos_inner {
+                    1.0
+                } else if cos_angle <= cos_outer {
+                    0.0
+                } else {
+                    // smoothstep
+                    let t = (cos_angle - cos_outer) / (cos_inner - cos_outer);
+                    t * t * (3.0 - 2.0 * t)
+                }
+            }
+            _ => 1.0,
+        };
+        let falloff = match self.light_type {
+            LightType::Directional { .. } => 1.0,
+            _ => self.falloff.at(self.distance_from(point)),
+        };
+        self.color * (self.power * falloff * cone)
+    }
+
+    /// Point on the light for `u` and `v` in `[0, 1]`, evenly spread over its area
+    pub fn point_on(&self, u: f64, v: f64) -> Point {
+        match self.light_type {
+            LightType::Point | LightType::Spot { .. } | LightType::Directional { .. } => {
+                self.position
+            }
+            LightType::Rectangle {
+                u: edge_u,
+                v: edge_v,
+            } => self.position + edge_u * (u - 0.5) + edge_v * (v - 0.5),
+            LightType::Disk { radius, normal } => {
+                let (x, y) = sampling::concentric_disk(u, v);
+                let (tangent, bitangent) = sampling::basis_around(normal);
+                self.position + (tangent * x + bitangent * y) * radius
+            }
+            LightType::Sphere { radius } => {
+                let (x, y, z) = sampling::uniform_sphere(u, v);
+                self.position + Vector::new(x, y, z) * radius
+            }
+        }
+    }
+
+    /// `samples` (rounded up to a square) points on area lights, stratified and jittered.
+    /// Just `position` for the others
+    pub fn sample_points(&self, rng: &mut Pcg) -> Vec<Point> {
+        if !self.is_area() {
+            return vec![self.position];
+        }
+        SamplePattern::Jittered
+            .positions(self.samples, rng)
+            .into_iter()
+            .map(|(u, v)| self.point_on(u, v))
+            .collect()
     }
 }
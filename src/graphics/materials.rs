@@ -1,6 +1,7 @@
 use super::{
     color::{self, Color},
-    lights::PointLight,
+    lights::Light,
+    media::Medium,
     patterns::Pattern,
 };
 use crate::{
@@ -30,6 +31,54 @@ pub struct Material {
     pub refractive_index: f64,
     /// Pattern
     pub pattern: Option<Pattern>,
+    /// wavelength dependent `refractive_index`, only used in spectral mode
+    /// (`RenderSettings::spectral`). Defaults to `None`
+    pub dispersion: Option<Dispersion>,
+    /// fills the (closed) `Shape` with fog or smoke, its surface is then invisible
+    /// and only the volume inside shows. Defaults to `None`
+    pub medium: Option<Medium>,
+    /// color transparent objects tint light to after it travelled `absorption_distance`
+    /// inside them, thicker glass is darker. Defaults to white (clear)
+    pub absorption: Color,
+    /// see `absorption`, defaults to `1.0`
+    pub absorption_distance: f64,
+}
+
+/// Refractive index as a function of wavelength, splits white light into a rainbow
+#[derive(Clone, PartialEq, Copy, Debug)]
+pub enum Dispersion {
+    /// `n = a + b / wavelength^2`, `wavelength` in micrometers.
+    /// Crown glass (BK7) is about `a = 1.5046, b = 0.0042`
+    Cauchy { a: f64, b: f64 },
+    /// `n^2 = 1 + sum(b * wavelength^2 / (wavelength^2 - c))`, `wavelength` in micrometers
+    /// and `c` in micrometers squared. Matches measured glasses over the visible range
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl Dispersion {
+    /// BK7 crown glass
+    pub const CROWN_GLASS: Dispersion = Dispersion::Sellmeier {
+        b: [1.03961212, 0.231792344, 1.01046945],
+        c: [0.00600069867, 0.0200179144, 103.560653],
+    };
+    /// Diamond, strong dispersion (fire)
+    pub const DIAMOND: Dispersion = Dispersion::Sellmeier {
+        b: [0.3306, 4.3356, 0.0],
+        c: [0.030625, 0.011236, 0.0],
+    };
+
+    /// refractive index at `wavelength` (nanometers)
+    pub fn index(&self, wavelength: f64) -> f64 {
+        let micrometers = wavelength / 1000.0;
+        let l2 = micrometers * micrometers;
+        match self {
+            Dispersion::Cauchy { a, b } => a + b / l2,
+            Dispersion::Sellmeier { b, c } => {
+                let sum: f64 = b.iter().zip(c).map(|(b, c)| b * l2 / (l2 - c)).sum();
+                (1.0 + sum).sqrt()
+            }
+        }
+    }
 }
 
 impl Material {
@@ -54,6 +103,10 @@ impl Material {
             transparency,
             refractive_index,
             pattern,
+            dispersion: None,
+            medium: None,
+            absorption: Color::new(1.0, 1.0, 1.0),
+            absorption_distance: 1.0,
         }
     }
     /// default material
@@ -68,35 +121,64 @@ impl Material {
             transparency: 0.0,
             refractive_index: 1.0,
             pattern: None,
+            dispersion: None,
+            medium: None,
+            absorption: Color::new(1.0, 1.0, 1.0),
+            absorption_distance: 1.0,
+        }
+    }
+
+    /// `refractive_index`, or `dispersion` at `wavelength` (nanometers) in spectral mode
+    pub fn refractive_index_at(&self, wavelength: Option<f64>) -> f64 {
+        match (self.dispersion, wavelength) {
+            (Some(dispersion), Some(wavelength)) => dispersion.index(wavelength),
+            _ => self.refractive_index,
         }
     }
+    /// share of the light left after travelling `distance` inside, per channel (Beer-Lambert)
+    pub fn transmittance(&self, distance: f64) -> Color {
+        let scale = distance / self.absorption_distance;
+        Color::new(
+            self.absorption.red.powf(scale),
+            self.absorption.green.powf(scale),
+            self.absorption.blue.powf(scale),
+        )
+    }
+
+    /// `color`, or the `pattern` at `position` (*world-coordinates*) if there is one
+    pub fn color_at(&self, object: &Shape, position: Point) -> Color {
+        // apply pattern
+        if self.pattern.is_some() {
+            object.pattern_at(position).unwrap()
+        } else {
+            self.color
+        }
+    }
+
     // TODO: move to object?
+    /// Phong shading, `light_attenuation` is the share of the light's color reaching
+    /// `position`, `color::BLACK` in shadow. See `World::light_transmittance`
     pub fn lighting(
         &self,
         object: Shape,
-        light: PointLight,
+        light: Light,
         position: Point,
         eye: Vector,
         normal: Vector,
-        in_shadow: bool,
+        light_attenuation: Color,
     ) -> Color {
         let diffuse;
         let specular;
         
-        let color;
-
-        // apply pattern
-        if self.pattern.is_some() {
-            color = object.pattern_at(position).unwrap();
-        } else {
-            color = self.color
-        };
+        let color = self.color_at(&object, position);
 
-        // combine the surface color with the light's color
-        let effective_color = color * light.color;
+        // combine the surface color with the light's color,
+        // dimmed outside of spot lights' cones
+        let intensity = light.intensity_at(position);
+        let effective_color = color * intensity;
 
         // find the direction of the light source
-        let lightv = (light.position - position).normalize();
+        let lightv = light.direction_from(position);
 
         // compute the ambient contribution
         let ambient = effective_color * self.ambient;
@@ -106,7 +188,7 @@ impl Material {
         // that the light is on the other side of the surface
         let light_dot_normal = lightv.dot_product(&normal);
 
-        if in_shadow || light_dot_normal < 0.0 {
+        if light_dot_normal < 0.0 {
             diffuse = color::BLACK;
             specular = color::BLACK;
         } else {
@@ -123,10 +205,11 @@ impl Material {
                 specular = color::BLACK
             } else {
                 let factor = reflect_dot_eye.powf(self.shininess);
-                specular = light.color * self.specular * factor;
+                specular = intensity * self.specular * factor;
             }
         };
-        // add three contributions together to get the final shading
-        ambient + diffuse + specular
+        // add three contributions together to get the final shading,
+        // less of the light reaches surfaces in (partial) shadow
+        ambient + diffuse * light_attenuation + specular * light_attenuation
     }
 }
@@ -0,0 +1,65 @@
+use std::f64::consts::PI;
+
+use super::color::Color;
+
+/// Homogeneous participating medium (fog, smoke, murky water...), light passing through
+/// is absorbed and scattered the same everywhere inside it.
+/// Fills the whole scene as `World::atmosphere`, or a closed `Shape` as `Material::medium`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Medium {
+    /// fraction of light absorbed per unit distance, defaults to `0.0`
+    pub absorption: f64,
+    /// fraction of light scattered per unit distance, defaults to `0.1`
+    pub scattering: f64,
+    /// tint of the scattered light, defaults to white
+    pub color: Color,
+    /// Henyey-Greenstein asymmetry, between `-1` and `1`. `0` scatters evenly,
+    /// positive mostly forward (glow around lights seen through fog)
+    /// and negative mostly back. Defaults to `0.0`
+    pub anisotropy: f64,
+}
+
+impl Default for Medium {
+    fn default() -> Self {
+        Self {
+            absorption: 0.0,
+            scattering: 0.1,
+            color: Color::new(1.0, 1.0, 1.0),
+            anisotropy: 0.0,
+        }
+    }
+}
+
+impl Medium {
+    pub fn new(absorption: f64, scattering: f64, color: Color, anisotropy: f64) -> Self {
+        Self {
+            absorption,
+            scattering,
+            color,
+            anisotropy,
+        }
+    }
+
+    /// light lost per unit distance, `absorption + scattering`
+    pub fn extinction(&self) -> f64 {
+        self.absorption + self.scattering
+    }
+
+    /// fraction of light left after travelling `distance` through the medium (Beer-Lambert)
+    pub fn transmittance(&self, distance: f64) -> f64 {
+        (-self.extinction() * distance).exp()
+    }
+
+    /// `henyey_greenstein` with this medium's `anisotropy`
+    pub fn phase(&self, cos_theta: f64) -> f64 {
+        henyey_greenstein(cos_theta, self.anisotropy)
+    }
+}
+
+/// Henyey-Greenstein phase function, the share of scattered light that leaves
+/// at angle `theta` from the direction it was travelling in (per steradian).
+/// `g` is the asymmetry, see `Medium::anisotropy`
+pub fn henyey_greenstein(cos_theta: f64, g: f64) -> f64 {
+    let denominator = 1.0 + g * g - 2.0 * g * cos_theta;
+    (1.0 - g * g) / (4.0 * PI * denominator * denominator.sqrt())
+}
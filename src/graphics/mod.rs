@@ -1,7 +1,18 @@
+pub mod antialiasing;
+pub mod aov;
 pub mod camera;
 pub mod canvas;
 pub mod color;
+pub mod control;
+pub mod denoise;
+pub mod integrators;
 pub mod lights;
 pub mod materials;
+pub mod media;
 pub mod patterns;
+pub mod photon_map;
 pub mod ppm;
+pub mod settings;
+pub mod spectrum;
+pub mod stereo;
+pub mod volumes;
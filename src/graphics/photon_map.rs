@@ -0,0 +1,245 @@
+use std::f64::consts::PI;
+
+use crate::{
+    math::{
+        point::Point,
+        ray::{Ray, RayKind},
+        sampling::{self, Pcg},
+        vector::Vector,
+    },
+    objects::world::World,
+};
+
+use super::{
+    color::{self, Color},
+    lights::{Light, LightType},
+};
+
+/// Light that reached a diffuse surface after bouncing off, or through,
+/// a reflective or transparent one (a caustic)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Photon {
+    pub position: Point,
+    /// direction the photon was travelling in
+    pub direction: Vector,
+    pub power: Color,
+}
+
+/// Caustic photon map, built by tracing photons from `World::lights` before rendering.
+/// `World::shade_hit` adds the density of photons around every hit point.
+/// Photons are kept as a balanced kd-tree: the median of every slice is its node,
+/// split along `axes` of that node
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhotonMap {
+    photons: Vec<Photon>,
+    /// split axis of each node, `0`, `1` or `2` for `x`, `y` and `z`
+    axes: Vec<u8>,
+    /// photons within `radius` (world units) of a point are gathered
+    pub radius: f64,
+}
+
+impl PhotonMap {
+    /// Traces `count` photons (shared between `world.lights`) through its reflective and
+    /// transparent objects, keeping those that land on a diffuse surface.
+    /// A photon carries the light's intensity where it first lands (`Light::intensity_at`),
+    /// scaled by the area of the sphere around the light at that distance.
+    /// Directional lights have no position to emit from and cast no caustics.
+    /// Panics unless `radius` is positive
+    pub fn build(world: &World, count: usize, radius: f64) -> Self {
+        assert!(radius > 0.0, "PhotonMap radius must be positive!");
+        let mut photons = vec![];
+        let mut rng = Pcg::new(world.settings.seed, 0);
+        let emitting: Vec<&Light> = world
+            .lights
+            .iter()
+            .filter(|light| !matches!(light.light_type, LightType::Directional { .. }))
+            .collect();
+        // shared evenly between the lights
+        let count = count / emitting.len().max(1);
+
+        for light in emitting {
+            for _ in 0..count {
+                // area lights emit from anywhere on their surface
+                let origin = if light.is_area() {
+                    light.point_on(rng.next_f64(), rng.next_f64())
+                } else {
+                    light.position
+                };
+                let (x, y, z) = sampling::uniform_sphere(rng.next_f64(), rng.next_f64());
+                let mut ray = Ray {
+                    kind: RayKind::Light,
+                    ..Ray::new(origin, Vector::new(x, y, z))
+                };
+                let mut power = light.color;
+                let mut specular = false;
+
+                for depth in 0..world.settings.max_depth.max(0) {
+                    let xs = world.intersect(ray);
+                    let comps = match xs
+                        .hit()
+                        .and_then(|hit| hit.prepare_computations(ray, Some(&xs)))
+                    {
+                        Some(comps) => comps,
+                        None => break,
+                    };
+                    if depth == 0 {
+                        // 4 PI d^2 / count, photons spread over the sphere around the light.
+                        // Dark outside spot lights' cones, dimmer where the light falls off
+                        let d = comps.intersects_at;
+                        power = light.intensity_at(comps.point) * (4.0 * PI * d * d / count as f64);
+                    }
+                    let material = comps.object.material;
+                    power = power * comps.transmittance();
+
+                    // Russian roulette between the lobes, absorbs the rest
+                    let (reflective, transparent) = comps.lobe_weights();
+                    let pick = rng.next_f64();
+                    let over_point = comps.point + comps.normalv * world.settings.ray_bias;
+                    let under_point = comps.point - comps.normalv * world.settings.ray_bias;
+
+                    // bounces see what reflection and refraction rays see, like `World::reflected_color`
+                    let reflected =
+                        comps.secondary_ray(over_point, comps.reflectv, RayKind::Reflection);
+                    if pick < reflective {
+                        ray = reflected;
+                    } else if pick < reflective + transparent {
+                        ray = match comps.refracted_direction() {
+                            Some(direction) => {
+                                comps.secondary_ray(under_point, direction, RayKind::Refraction)
+                            }
+                            // total internal reflection
+                            None => reflected,
+                        };
+                    } else {
+                        if specular && material.diffuse > 0.0 {
+                            photons.push(Photon {
+                                position: comps.point,
+                                direction: ray.direction,
+                                power,
+                            });
+                        }
+                        break;
+                    }
+                    specular = true;
+                }
+            }
+        }
+        Self::from_photons(photons, radius)
+    }
+
+    /// Builds the kd-tree. Panics unless `radius` is positive
+    pub fn from_photons(photons: Vec<Photon>, radius: f64) -> Self {
+        assert!(radius > 0.0, "PhotonMap radius must be positive!");
+        let mut photons = photons;
+        let mut axes = vec![0; photons.len()];
+        balance(&mut photons, &mut axes);
+        Self {
+            photons,
+            axes,
+            radius,
+        }
+    }
+
+    /// number of stored photons
+    pub fn len(&self) -> usize {
+        self.photons.len()
+    }
+
+    /// true when no photon was stored
+    pub fn is_empty(&self) -> bool {
+        self.photons.is_empty()
+    }
+
+    /// Photons within `radius` of `point`
+    pub fn nearby(&self, point: Point, radius: f64) -> Vec<&Photon> {
+        let mut found = vec![];
+        self.search(0, self.photons.len(), point, radius, &mut found);
+        found
+    }
+
+    /// Caustic irradiance at `point` on a surface facing `normal`,
+    /// the power of the photons arriving on its front, over the gathering disk
+    pub fn irradiance(&self, point: Point, normal: Vector) -> Color {
+        // nothing gathered over no area
+        if self.photons.is_empty() || self.radius <= 0.0 {
+            return color::BLACK;
+        }
+        let area = PI * self.radius * self.radius;
+
+        self.nearby(point, self.radius)
+            .iter()
+            .filter(|photon| photon.direction.dot_product(&normal) < 0.0)
+            .fold(color::BLACK, |sum, photon| sum + photon.power)
+            * (1.0 / area)
+    }
+
+    /// range search in the slice `start..end` of the tree
+    fn search<'a>(
+        &'a self,
+        start: usize,
+        end: usize,
+        point: Point,
+        radius: f64,
+        found: &mut Vec<&'a Photon>,
+    ) {
+        if start >= end {
+            return;
+        }
+        let mid = start + (end - start) / 2;
+        let photon = &self.photons[mid];
+
+        if (photon.position - point).magnitude() <= radius {
+            found.push(photon);
+        }
+
+        let axis = self.axes[mid];
+        let offset = coordinate(point, axis) - coordinate(photon.position, axis);
+        // the side the point is on first, the other only if the sphere crosses the split
+        let (near, far) = if offset < 0.0 {
+            ((start, mid), (mid + 1, end))
+        } else {
+            ((mid + 1, end), (start, mid))
+        };
+        self.search(near.0, near.1, point, radius, found);
+        if offset.abs() <= radius {
+            self.search(far.0, far.1, point, radius, found);
+        }
+    }
+}
+
+/// orders `photons` into a balanced kd-tree, splitting along the widest axis
+fn balance(photons: &mut [Photon], axes: &mut [u8]) {
+    if photons.len() <= 1 {
+        return;
+    }
+    let extent = |axis: u8| {
+        let values = photons.iter().map(|p| coordinate(p.position, axis));
+        let max = values.clone().fold(f64::MIN, f64::max);
+        let min = values.fold(f64::MAX, f64::min);
+        max - min
+    };
+    let axis = (0..3)
+        .max_by(|&a, &b| extent(a).partial_cmp(&extent(b)).unwrap())
+        .unwrap();
+
+    let mid = photons.len() / 2;
+    photons.select_nth_unstable_by(mid, |a, b| {
+        coordinate(a.position, axis)
+            .partial_cmp(&coordinate(b.position, axis))
+            .unwrap()
+    });
+    axes[mid] = axis;
+
+    let (left, right) = photons.split_at_mut(mid);
+    let (left_axes, right_axes) = axes.split_at_mut(mid);
+    balance(left, left_axes);
+    balance(&mut right[1..], &mut right_axes[1..]);
+}
+
+fn coordinate(point: Point, axis: u8) -> f64 {
+    match axis {
+        0 => point.x,
+        1 => point.y,
+        _ => point.z,
+    }
+}
@@ -0,0 +1,53 @@
+use crate::constants;
+
+use super::{
+    color::{self, Color},
+    integrators::Integrators,
+};
+
+/// Per scene render settings, held by `World::settings`.
+/// Defaults to the crate wide `constants`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RenderSettings {
+    /// how many times rays can bounce (reflection, refraction or path length),
+    /// defaults to `constants::MAX_REFLECTION_RECRUSTION`
+    pub max_depth: isize,
+    /// offset from the surface where reflected and refracted rays start,
+    /// prevents "acne" from rays hitting the surface they left. Defaults to `constants::EPSILON`
+    pub ray_bias: f64,
+    /// same as `ray_bias`, for rays towards the light. Defaults to `constants::EPSILON`
+    pub shadow_bias: f64,
+    /// rays per pixel, defaults to 1 (no anti-aliasing)
+    pub samples: usize,
+    /// color of rays that miss every object, defaults to `color::BLACK`
+    pub background: Color,
+    /// threads the rows of `Camera::render` and `render_aovs` are split between, defaults to 1
+    pub threads: usize,
+    /// how the color along each ray is found, defaults to `Integrators::Whitted`
+    pub integrator: Integrators,
+    /// trace one random wavelength per ray instead of RGB, for dispersion
+    /// (`Material::dispersion`). Needs more `samples`. Defaults to `false`
+    pub spectral: bool,
+    /// lit points sampled along each ray segment inside a participating medium
+    /// (`World::atmosphere`, `Material::medium`), defaults to 8
+    pub volume_steps: usize,
+    /// seeds every random number, renders with the same seed are identical. Defaults to 0
+    pub seed: u64,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            max_depth: constants::MAX_REFLECTION_RECRUSTION,
+            ray_bias: constants::EPSILON,
+            shadow_bias: constants::EPSILON,
+            samples: 1,
+            background: color::BLACK,
+            threads: 1,
+            integrator: Integrators::default(),
+            spectral: false,
+            volume_steps: 8,
+            seed: 0,
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::sync::OnceLock;
+
+use super::color::Color;
+
+/// shortest visible wavelength traced in spectral mode, in nanometers
+pub const MIN_WAVELENGTH: f64 = 380.0;
+/// longest visible wavelength traced in spectral mode, in nanometers
+pub const MAX_WAVELENGTH: f64 = 780.0;
+
+/// CIE 1931 color matching functions `(x, y, z)` at `wavelength` (nanometers),
+/// using the multi-lobe Gaussian fit of Wyman, Sloan and Shirley
+pub fn cie_xyz(wavelength: f64) -> (f64, f64, f64) {
+    // piecewise Gaussian, different width on each side of `mean`
+    let g = |mean: f64, left: f64, right: f64| {
+        let sigma = if wavelength < mean { left } else { right };
+        (-0.5 * ((wavelength - mean) / sigma).powi(2)).exp()
+    };
+
+    let x =
+        1.056 * g(599.8, 37.9, 31.0) + 0.362 * g(442.0, 16.0, 26.7) - 0.065 * g(501.1, 20.4, 26.2);
+    let y = 0.821 * g(568.8, 46.9, 40.5) + 0.286 * g(530.9, 16.3, 31.1);
+    let z = 1.217 * g(437.0, 11.8, 36.0) + 0.681 * g(459.0, 26.0, 13.8);
+    (x, y, z)
+}
+
+/// CIE XYZ to linear sRGB (D65)
+pub fn xyz_to_rgb((x, y, z): (f64, f64, f64)) -> Color {
+    Color::new(
+        3.2406 * x - 1.5372 * y - 0.4986 * z,
+        -0.9689 * x + 1.8758 * y + 0.0415 * z,
+        0.0557 * x - 0.2040 * y + 1.0570 * z,
+    )
+}
+
+/// RGB weight of a single `wavelength` sample. Scaled so that the average over
+/// uniformly sampled visible wavelengths is white, so scenes without
+/// dispersion look the same in spectral mode (only noisier).
+/// Can be negative, outside the sRGB gamut
+pub fn wavelength_to_rgb(wavelength: f64) -> Color {
+    let white = white_balance();
+    let rgb = xyz_to_rgb(cie_xyz(wavelength));
+    Color::new(
+        rgb.red / white.red,
+        rgb.green / white.green,
+        rgb.blue / white.blue,
+    )
+}
+
+/// average RGB of the visible wavelengths, computed once
+fn white_balance() -> Color {
+    static WHITE: OnceLock<Color> = OnceLock::new();
+    *WHITE.get_or_init(|| {
+        let steps = 4000;
+        let step = (MAX_WAVELENGTH - MIN_WAVELENGTH) / steps as f64;
+        let sum = (0..steps).fold(Color::new(0.0, 0.0, 0.0), |sum, i| {
+            let wavelength = MIN_WAVELENGTH + (i as f64 + 0.5) * step;
+            sum + xyz_to_rgb(cie_xyz(wavelength))
+        });
+        sum * (1.0 / steps as f64)
+    })
+}
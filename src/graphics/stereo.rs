@@ -0,0 +1,129 @@
+use crate::{math::transformations::Transformation, objects::world::World};
+
+use super::{camera::Camera, canvas::Canvas, color::Color};
+
+/// Two `Camera`s side by side, one per eye, for stereo (VR) previews
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StereoCamera {
+    /// the rig's center, both eyes copy everything but the `transform`
+    pub camera: Camera,
+    /// distance between the eyes, in world units
+    pub interocular: f64,
+    /// distance from the rig where both eyes meet (zero parallax),
+    /// objects there appear on the screen, closer ones pop out of it.
+    /// `0.0` or less (or infinite) never meets, the eyes look straight ahead unshifted
+    pub convergence: f64,
+    /// how the eyes converge, defaults to `StereoMode::Parallel`
+    pub mode: StereoMode,
+    /// how the views are put in one `Canvas`, defaults to `StereoLayout::SideBySide`
+    pub layout: StereoLayout,
+}
+
+/// How the eyes converge
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StereoMode {
+    /// both eyes look straight ahead and their canvases are shifted (off-axis),
+    /// no vertical parallax
+    Parallel,
+    /// both eyes are turned towards the convergence point,
+    /// simpler but adds vertical parallax at the edges
+    ToeIn,
+}
+
+/// How the left and right views are put in one `Canvas`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StereoLayout {
+    /// left view on the left, twice as wide
+    SideBySide,
+    /// left view on top, twice as tall
+    TopBottom,
+    /// red/cyan glasses, red from the left view and green and blue from the right
+    Anaglyph,
+}
+
+impl StereoCamera {
+    pub fn new(camera: Camera, interocular: f64, convergence: f64) -> Self {
+        Self {
+            camera,
+            interocular,
+            convergence,
+            mode: StereoMode::Parallel,
+            layout: StereoLayout::SideBySide,
+        }
+    }
+
+    /// Camera for the left eye
+    pub fn left(&self) -> Camera {
+        self.eye(1.0)
+    }
+
+    /// Camera for the right eye
+    pub fn right(&self) -> Camera {
+        self.eye(-1.0)
+    }
+
+    /// `side` is `1.0` for left and `-1.0` for right,
+    /// the camera's `x` axis points to the left
+    fn eye(&self, side: f64) -> Camera {
+        let offset = side * self.interocular / 2.0;
+        let mut eye = self.camera;
+
+        // move the eye along the camera's x axis
+        eye.transform = Transformation::translation(-offset, 0.0, 0.0) * self.camera.transform;
+
+        // converging at infinity, parallel eyes
+        if !(self.convergence > 0.0 && self.convergence.is_finite()) {
+            return eye;
+        }
+
+        match self.mode {
+            StereoMode::Parallel => {
+                // slide the canvas so the convergence point is in its center
+                eye.shift_x = self.camera.shift_x - offset / self.convergence;
+            }
+            StereoMode::ToeIn => {
+                let angle = (offset / self.convergence).atan();
+                eye.transform = Transformation::rotate_y(-angle) * eye.transform;
+            }
+        }
+        eye
+    }
+
+    /// renders both eyes and composes them using `layout`
+    pub fn render(&self, world: World) -> Canvas {
+        let left = self.left().render(world.clone());
+        let right = self.right().render(world);
+        self.compose(&left, &right)
+    }
+
+    /// puts `left` and `right` (same size) in one `Canvas` using `layout`
+    pub fn compose(&self, left: &Canvas, right: &Canvas) -> Canvas {
+        let (width, height) = (left.width, left.height);
+
+        match self.layout {
+            StereoLayout::SideBySide => {
+                let mut canvas = Canvas::new(width * 2, height);
+                (0..width * height).for_each(|i| {
+                    let (x, y) = (i % width, i / width);
+                    canvas.write(x, y, left.grid[i]);
+                    canvas.write(x + width, y, right.grid[i]);
+                });
+                canvas
+            }
+            StereoLayout::TopBottom => {
+                let mut canvas = Canvas::new(width, height * 2);
+                canvas.grid[..width * height].copy_from_slice(&left.grid);
+                canvas.grid[width * height..].copy_from_slice(&right.grid);
+                canvas
+            }
+            StereoLayout::Anaglyph => {
+                let mut canvas = Canvas::new(width, height);
+                (0..width * height).for_each(|i| {
+                    let (l, r) = (left.grid[i], right.grid[i]);
+                    canvas.write_i(i, Color::new(l.red, r.green, r.blue));
+                });
+                canvas
+            }
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use std::{io, path::Path};
+
+use crate::math::{point::Point, sampling::Pcg};
+
+/// Density of a heterogeneous volume at a point in *object-space*, see `Shape::density`.
+/// Scales the `absorption` and `scattering` of the `Shape`'s `Material::medium`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Density {
+    /// sampled from voxels
+    Grid(VoxelGrid),
+    /// procedural clouds
+    Noise(NoiseField),
+}
+
+impl Density {
+    /// density at `object_point`, never negative
+    pub fn at(&self, object_point: Point) -> f64 {
+        match self {
+            Density::Grid(grid) => grid.at(object_point),
+            Density::Noise(noise) => noise.at(object_point),
+        }
+    }
+
+    /// largest density anywhere, bounds the density for delta and ratio tracking
+    pub fn max(&self) -> f64 {
+        match self {
+            Density::Grid(grid) => grid.max,
+            Density::Noise(_) => 1.0,
+        }
+    }
+}
+
+/// Densities on a regular 3d grid filling the cube from `(-1, -1, -1)` to `(1, 1, 1)`
+/// (same as `cube::default()`), interpolated between voxel centers. Zero outside
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoxelGrid {
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+    /// `x` changes fastest, then `y`, then `z`
+    values: Vec<f64>,
+    max: f64,
+}
+
+impl VoxelGrid {
+    /// `None` unless there are `width * height * depth` values
+    pub fn new(width: usize, height: usize, depth: usize, values: Vec<f64>) -> Option<Self> {
+        if values.len() != width * height * depth || values.is_empty() {
+            return None;
+        }
+        let values: Vec<f64> = values.into_iter().map(|v| v.max(0.0)).collect();
+        let max = values.iter().cloned().fold(0.0, f64::max);
+        Some(Self {
+            width,
+            height,
+            depth,
+            values,
+            max,
+        })
+    }
+
+    /// Raw format: one byte per voxel, `0` is empty and `255` a density of `1.0`,
+    /// `x` fastest, then `y`, then `z`. No header, the size has to be known
+    pub fn from_raw(bytes: &[u8], width: usize, height: usize, depth: usize) -> Option<Self> {
+        let values = bytes.iter().map(|&b| b as f64 / 255.0).collect();
+        Self::new(width, height, depth, values)
+    }
+
+    /// Reads a raw file, see `from_raw`
+    pub fn load_raw(
+        path: impl AsRef<Path>,
+        width: usize,
+        height: usize,
+        depth: usize,
+    ) -> io::Result<Self> {
+        let bytes = std::fs::read(path)?;
+        Self::from_raw(&bytes, width, height, depth).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "expected {} voxels, found {}",
+                    width * height * depth,
+                    bytes.len()
+                ),
+            )
+        })
+    }
+
+    /// value of voxel (`x`, `y`, `z`)
+    pub fn get(&self, x: usize, y: usize, z: usize) -> f64 {
+        self.values[(z * self.height + y) * self.width + x]
+    }
+
+    /// trilinear interpolation between the 8 closest voxel centers
+    pub fn at(&self, object_point: Point) -> f64 {
+        let (x, y, z) = (object_point.x, object_point.y, object_point.z);
+        if !(-1.0..=1.0).contains(&x) || !(-1.0..=1.0).contains(&y) || !(-1.0..=1.0).contains(&z) {
+            return 0.0;
+        }
+        // continuous voxel coordinates, voxel centers are on integers
+        let grid = |v: f64, size: usize| {
+            let g = ((v + 1.0) / 2.0 * size as f64 - 0.5).clamp(0.0, (size - 1) as f64);
+            let i = (g.floor() as usize).min(size.saturating_sub(2));
+            (i, (i + 1).min(size - 1), g - i as f64)
+        };
+        let (x0, x1, fx) = grid(x, self.width);
+        let (y0, y1, fy) = grid(y, self.height);
+        let (z0, z1, fz) = grid(z, self.depth);
+
+        let plane = |z: usize| {
+            lerp(
+                fy,
+                lerp(fx, self.get(x0, y0, z), self.get(x1, y0, z)),
+                lerp(fx, self.get(x0, y1, z), self.get(x1, y1, z)),
+            )
+        };
+        lerp(fz, plane(z0), plane(z1))
+    }
+}
+
+/// largest `NoiseField::threshold`, at `1` no noise would be left to rescale
+const MAX_THRESHOLD: f64 = 0.999;
+
+/// Clouds from fractal Perlin noise
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoiseField {
+    /// features per unit of *object-space*, larger is finer
+    pub frequency: f64,
+    /// layers of finer noise added on top, more is more detailed
+    pub octaves: usize,
+    /// noise below this (in `[0, 1)`) is empty space, larger leaves smaller and sparser clouds.
+    /// Capped just below `1`
+    pub threshold: f64,
+    /// shuffled `0..256`, twice, so lookups don't need to wrap
+    permutation: Vec<u8>,
+}
+
+impl NoiseField {
+    /// same `seed` same clouds, `threshold` is capped just below `1`
+    pub fn new(seed: u64, frequency: f64, octaves: usize, threshold: f64) -> Self {
+        let mut rng = Pcg::new(seed, 0);
+        let mut table: Vec<u8> = (0..=255).collect();
+        // Fisher-Yates
+        for i in (1..table.len()).rev() {
+            let j = (rng.next_f64() * (i + 1) as f64) as usize;
+            table.swap(i, j);
+        }
+        let permutation = table.iter().chain(table.iter()).cloned().collect();
+        Self {
+            frequency,
+            octaves,
+            threshold: threshold.min(MAX_THRESHOLD),
+            permutation,
+        }
+    }
+
+    /// density in `[0, 1]`
+    pub fn at(&self, object_point: Point) -> f64 {
+        let mut sum = 0.0;
+        let mut amplitude = 1.0;
+        let mut total = 0.0;
+        let mut frequency = self.frequency;
+        for _ in 0..self.octaves.max(1) {
+            let p = object_point;
+            sum += amplitude * self.perlin(p.x * frequency, p.y * frequency, p.z * frequency);
+            total += amplitude;
+            amplitude /= 2.0;
+            frequency *= 2.0;
+        }
+        // from about [-1, 1] to [0, 1]
+        let noise = (sum / total + 1.0) / 2.0;
+        let threshold = self.threshold.min(MAX_THRESHOLD);
+        ((noise - threshold) / (1.0 - threshold)).clamp(0.0, 1.0)
+    }
+
+    /// Ken Perlin's improved noise, about `[-1, 1]`
+    pub fn perlin(&self, x: f64, y: f64, z: f64) -> f64 {
+        let p = &self.permutation;
+        let cell = |v: f64| (v.floor() as i64 & 255) as usize;
+        let (xi, yi, zi) = (cell(x), cell(y), cell(z));
+        let (x, y, z) = (x - x.floor(), y - y.floor(), z - z.floor());
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let a = p[xi] as usize + yi;
+        let (aa, ab) = (p[a] as usize + zi, p[a + 1] as usize + zi);
+        let b = p[xi + 1] as usize + yi;
+        let (ba, bb) = (p[b] as usize + zi, p[b + 1] as usize + zi);
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, grad(p[aa], x, y, z), grad(p[ba], x - 1.0, y, z)),
+                lerp(
+                    u,
+                    grad(p[ab], x, y - 1.0, z),
+                    grad(p[bb], x - 1.0, y - 1.0, z),
+                ),
+            ),
+            lerp(
+                v,
+                lerp(
+                    u,
+                    grad(p[aa + 1], x, y, z - 1.0),
+                    grad(p[ba + 1], x - 1.0, y, z - 1.0),
+                ),
+                lerp(
+                    u,
+                    grad(p[ab + 1], x, y - 1.0, z - 1.0),
+                    grad(p[bb + 1], x - 1.0, y - 1.0, z - 1.0),
+                ),
+            ),
+        )
+    }
+}
+
+/// `6t^5 - 15t^4 + 10t^3`, smooth at both ends
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+/// dot product with one of 12 gradient directions, picked by `hash`
+fn grad(hash: u8, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
@@ -1,5 +1,6 @@
 pub mod matrix;
 pub mod point;
 pub mod ray;
+pub mod sampling;
 pub mod transformations;
 pub mod vector;
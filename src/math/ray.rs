@@ -4,12 +4,44 @@ use super::{point::Point, transformations::Transformation, vector::Vector};
 pub struct Ray {
     pub origin: Point,
     pub direction: Vector,
+    /// moment the ray is cast at, between the camera's shutter open and close.
+    /// Animated `Shape`s are intersected where they are at this time
+    pub time: f64,
+    /// wavelength in nanometers, set in spectral mode. `None` carries every wavelength (RGB)
+    pub wavelength: Option<f64>,
+    /// what the ray is for, decides which `Shape`s it sees (`Shape::visibility`)
+    pub kind: RayKind,
+}
+
+/// Where a ray comes from
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum RayKind {
+    /// from the camera, the default
+    #[default]
+    Camera,
+    /// bounced off a surface (mirror or diffuse)
+    Reflection,
+    /// through a transparent surface
+    Refraction,
+    /// leaving a light (photons), sees what `Visibility::casts_shadows`
+    Light,
 }
 
 impl Ray {
-    /// Returns a new `Ray` struct
+    /// Returns a new `Ray` struct, cast at time `0.0`
     pub fn new(origin: Point, direction: Vector) -> Ray {
-        Ray { origin, direction }
+        Ray::new_at_time(origin, direction, 0.0)
+    }
+
+    /// Returns a new `Ray` struct, cast at `time`
+    pub fn new_at_time(origin: Point, direction: Vector, time: f64) -> Ray {
+        Ray {
+            origin,
+            direction,
+            time,
+            wavelength: None,
+            kind: RayKind::Camera,
+        }
     }
 
     /// Returns the `Point` in  `distance` from the `origin: Point` in `direction: Vector`
@@ -21,6 +53,7 @@ impl Ray {
         Ray {
             origin: transformation * self.origin,
             direction: transformation * self.direction,
+            ..*self
         }
     }
 }
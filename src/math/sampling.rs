@@ -0,0 +1,242 @@
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+use super::{ray::Ray, vector::Vector};
+
+/// Permuted Congruential Generator (PCG32, XSH-RR variant).
+/// Small, fast and seedable, so stochastic renders are reproducible.
+/// `stream` selects one of 2^63 independent sequences for the same `seed`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pcg {
+    state: u64,
+    inc: u64,
+}
+
+const MULTIPLIER: u64 = 6364136223846793005;
+
+impl Pcg {
+    /// Returns a new generator for `seed` on `stream`
+    pub fn new(seed: u64, stream: u64) -> Self {
+        let mut pcg = Pcg {
+            state: 0,
+            inc: (stream << 1) | 1,
+        };
+        pcg.next_u32();
+        pcg.state = pcg.state.wrapping_add(seed);
+        pcg.next_u32();
+        pcg
+    }
+
+    /// Generator for pixel (`x`, `y`) of an image `width` pixels wide,
+    /// every pixel gets its own stream so the order pixels (or threads) run in doesn't matter
+    pub fn for_pixel(seed: u64, x: usize, y: usize, width: usize) -> Self {
+        Pcg::new(seed, (y * width + x) as u64)
+    }
+
+    /// Generator for `ray`, for when no generator is handed down (`World::color_at`).
+    /// The same ray always gets the same numbers
+    pub fn for_ray(seed: u64, ray: &Ray) -> Self {
+        let (o, d) = (ray.origin, ray.direction);
+        // FNV-1a over the bits
+        let hash = [o.x, o.y, o.z, d.x, d.y, d.z, ray.time]
+            .iter()
+            .fold(0xcbf29ce484222325, |hash: u64, v| {
+                (hash ^ v.to_bits()).wrapping_mul(0x100000001b3)
+            });
+        Pcg::new(seed, hash)
+    }
+
+    /// Returns a new independent generator, seeded from this one.
+    /// For handing one to every thread
+    pub fn split(&mut self) -> Self {
+        let seed = (self.next_u32() as u64) << 32 | self.next_u32() as u64;
+        let stream = (self.next_u32() as u64) << 32 | self.next_u32() as u64;
+        Pcg::new(seed, stream)
+    }
+
+    /// Next uniformly distributed `u32`
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(MULTIPLIER).wrapping_add(self.inc);
+
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rot = (old >> 59) as u32;
+        xorshifted.rotate_right(rot)
+    }
+
+    /// Next uniformly distributed `f64` in `[0, 1)`
+    pub fn next_f64(&mut self) -> f64 {
+        // 32 bits of randomness is plenty for sampling
+        self.next_u32() as f64 / (u32::MAX as f64 + 1.0)
+    }
+}
+
+/// Van der Corput radical inverse of `index` in `base`,
+/// mirrors the digits of `index` around the decimal point.
+/// Used to build the Halton sequence (one prime base per dimension).
+pub fn radical_inverse(base: u64, index: u64) -> f64 {
+    let inv_base = 1.0 / base as f64;
+    let mut index = index;
+    let mut inv_base_n = 1.0;
+    let mut reversed = 0.0;
+
+    while index > 0 {
+        let digit = index % base;
+        inv_base_n *= inv_base;
+        reversed += digit as f64 * inv_base_n;
+        index /= base;
+    }
+    reversed
+}
+
+/// first primes, the Halton bases
+const PRIMES: [u64; 16] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53];
+
+/// `index`th point of the Halton sequence, coordinate `dimension` (below 16).
+/// Each dimension uses the next prime as its base
+pub fn halton(index: u64, dimension: usize) -> f64 {
+    radical_inverse(PRIMES[dimension % PRIMES.len()], index)
+}
+
+/// `(s, a, m)` of the primitive polynomials for Sobol dimensions 1 to 7 (Joe and Kuo),
+/// dimension 0 is the Van der Corput sequence in base 2
+const SOBOL_POLYNOMIALS: [(usize, u32, &[u32]); 7] = [
+    (1, 0, &[1]),
+    (2, 1, &[1, 3]),
+    (3, 1, &[1, 3, 1]),
+    (3, 2, &[1, 1, 1]),
+    (4, 1, &[1, 1, 3, 3]),
+    (4, 4, &[1, 3, 5, 13]),
+    (5, 2, &[1, 1, 5, 5, 17]),
+];
+
+/// `index`th point of the Sobol sequence, coordinate `dimension` (below 8).
+/// Better stratified than Halton in the first dimensions, any power of two
+/// number of points is evenly spread
+pub fn sobol(index: u32, dimension: usize) -> f64 {
+    let directions = sobol_directions(dimension % (SOBOL_POLYNOMIALS.len() + 1));
+
+    let mut index = index;
+    let mut result = 0;
+    let mut bit = 0;
+    while index > 0 {
+        if index & 1 == 1 {
+            result ^= directions[bit];
+        }
+        index >>= 1;
+        bit += 1;
+    }
+    result as f64 / (u32::MAX as f64 + 1.0)
+}
+
+/// direction numbers, one per bit of the index
+fn sobol_directions(dimension: usize) -> [u32; 32] {
+    let mut v = [0; 32];
+    if dimension == 0 {
+        (0..32).for_each(|k| v[k] = 1 << (31 - k));
+        return v;
+    }
+
+    let (s, a, m) = SOBOL_POLYNOMIALS[dimension - 1];
+    for k in 0..32 {
+        v[k] = if k < s {
+            m[k] << (31 - k)
+        } else {
+            let mut d = v[k - s] ^ (v[k - s] >> s);
+            for j in 1..s {
+                if (a >> (s - 1 - j)) & 1 == 1 {
+                    d ^= v[k - j];
+                }
+            }
+            d
+        };
+    }
+    v
+}
+
+/// Maps `u` and `v` in `[0, 1)` to a point on the unit disk,
+/// using Shirley's concentric mapping (keeps strata intact, unlike `sqrt(u)`)
+pub fn concentric_disk(u: f64, v: f64) -> (f64, f64) {
+    // map to [-1, 1]
+    let a = 2.0 * u - 1.0;
+    let b = 2.0 * v - 1.0;
+
+    if a == 0.0 && b == 0.0 {
+        return (0.0, 0.0);
+    }
+
+    let (radius, theta) = if a.abs() > b.abs() {
+        (a, FRAC_PI_4 * (b / a))
+    } else {
+        (b, FRAC_PI_2 - FRAC_PI_4 * (a / b))
+    };
+    (radius * theta.cos(), radius * theta.sin())
+}
+
+/// Maps `u` and `v` in `[0, 1)` to a point inside a regular polygon with `sides`
+/// corners on the unit circle, the first corner is at angle `rotation` (radians)
+pub fn regular_polygon(sides: usize, rotation: f64, u: f64, v: f64) -> (f64, f64) {
+    let sides = sides.max(3);
+
+    // every side forms a triangle with the center, all with the same area
+    let scaled = u * sides as f64;
+    let side = (scaled.floor() as usize).min(sides - 1);
+    let u = scaled - side as f64;
+
+    let angle = |i: usize| rotation + 2.0 * PI * i as f64 / sides as f64;
+    let (a, b) = (angle(side), angle(side + 1));
+
+    // uniform point in the triangle (center, a, b)
+    let (s, t) = if u + v > 1.0 {
+        (1.0 - u, 1.0 - v)
+    } else {
+        (u, v)
+    };
+    (s * a.cos() + t * b.cos(), s * a.sin() + t * b.sin())
+}
+
+/// Maps `u` and `v` in `[0, 1)` to a direction `(x, y, z)` on the hemisphere around +z,
+/// denser towards the pole with pdf `cos(theta) / PI` (Malley's method)
+pub fn cosine_hemisphere(u: f64, v: f64) -> (f64, f64, f64) {
+    let (x, y) = concentric_disk(u, v);
+    let z = (1.0 - x * x - y * y).max(0.0).sqrt();
+    (x, y, z)
+}
+
+/// Two unit vectors `(tangent, bitangent)` perpendicular to (normalized) `normal`
+/// and to each other, to turn samples around +z into samples around `normal`
+pub fn basis_around(normal: Vector) -> (Vector, Vector) {
+    // any vector that is not parallel to the normal
+    let helper = if normal.x.abs() > 0.9 {
+        Vector::new(0.0, 1.0, 0.0)
+    } else {
+        Vector::new(1.0, 0.0, 0.0)
+    };
+    let tangent = helper.cross_product(&normal).normalize();
+    let bitangent = normal.cross_product(&tangent);
+    (tangent, bitangent)
+}
+
+/// Maps `u` and `v` in `[0, 1)` to a direction `(x, y, z)` on the hemisphere around +z,
+/// every direction equally likely, pdf `1 / (2 * PI)`
+pub fn uniform_hemisphere(u: f64, v: f64) -> (f64, f64, f64) {
+    let z = u;
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    let phi = 2.0 * PI * v;
+    (r * phi.cos(), r * phi.sin(), z)
+}
+
+/// Maps `u` and `v` in `[0, 1)` to a direction `(x, y, z)` on the unit sphere,
+/// every direction equally likely, pdf `1 / (4 * PI)`
+pub fn uniform_sphere(u: f64, v: f64) -> (f64, f64, f64) {
+    let z = 1.0 - 2.0 * u;
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    let phi = 2.0 * PI * v;
+    (r * phi.cos(), r * phi.sin(), z)
+}
+
+/// Maps `u` and `v` in `[0, 1)` to barycentric coordinates `(b0, b1)`
+/// of a uniformly distributed point in a triangle, `b2 = 1 - b0 - b1`
+pub fn uniform_triangle(u: f64, v: f64) -> (f64, f64) {
+    let su = u.sqrt();
+    (1.0 - su, v * su)
+}
@@ -138,6 +138,274 @@ impl Transformation {
     }
 }
 
+/// `Transformation` of an animated object at `time`.
+/// Split into translation, rotation and scale once, when it is created,
+/// so rays don't have to do it again for every intersection
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Keyframe {
+    time: f64,
+    transformation: Transformation,
+    parts: Option<Parts>,
+}
+
+impl Keyframe {
+    pub fn new(time: f64, transformation: Transformation) -> Self {
+        Keyframe {
+            time,
+            transformation,
+            parts: decompose(&transformation),
+        }
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn transformation(&self) -> Transformation {
+        self.transformation
+    }
+}
+
+impl Transformation {
+    /// Blends `self` (at `t = 0.0`) into `other` (at `t = 1.0`).
+    /// Both are split into translation, rotation and scale first,
+    /// so rotating objects turn along the arc instead of shrinking half way.
+    pub fn interpolate(&self, other: &Transformation, t: f64) -> Transformation {
+        blend(
+            self,
+            decompose(self).as_ref(),
+            other,
+            decompose(other).as_ref(),
+            t,
+        )
+    }
+
+    /// `Transformation` at `time`, between the two keyframes around it.
+    /// `keyframes` must be sorted by time, before the first and after the last
+    /// keyframe the object stands still. `None` if there are no keyframes.
+    pub fn at_time(keyframes: &[Keyframe], time: f64) -> Option<Transformation> {
+        let first = keyframes.first()?;
+        let last = keyframes.last()?;
+
+        if time <= first.time {
+            return Some(first.transformation);
+        }
+        if time >= last.time {
+            return Some(last.transformation);
+        }
+
+        keyframes.windows(2).find_map(|pair| {
+            let (from, to) = (pair[0], pair[1]);
+            if from.time <= time && time <= to.time {
+                let t = (time - from.time) / (to.time - from.time);
+                Some(blend(
+                    &from.transformation,
+                    from.parts.as_ref(),
+                    &to.transformation,
+                    to.parts.as_ref(),
+                    t,
+                ))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// unit quaternion (w, x, y, z)
+type Quaternion = [f64; 4];
+
+/// `M = T * R * S`, see `decompose`
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Parts {
+    translation: Vector,
+    rotation: Quaternion,
+    scale: Matrix,
+}
+
+/// `Transformation::interpolate` with the parts of `from` and `to` already decomposed
+fn blend(
+    from: &Transformation,
+    from_parts: Option<&Parts>,
+    to: &Transformation,
+    to_parts: Option<&Parts>,
+    t: f64,
+) -> Transformation {
+    match (from_parts, to_parts) {
+        (Some(a), Some(b)) => {
+            let translation = a.translation + (b.translation - a.translation) * t;
+            let rotation = slerp(a.rotation, b.rotation, t);
+
+            let mut scale = Matrix::identity();
+            (0..3).for_each(|row| {
+                (0..3).for_each(|column| {
+                    let (s0, s1) = (a.scale.get(row, column), b.scale.get(row, column));
+                    scale.write(row, column, s0 + (s1 - s0) * t);
+                })
+            });
+
+            Transformation::translation(translation.x, translation.y, translation.z)
+                * Transformation {
+                    matrix: rotation_matrix(rotation),
+                }
+                * Transformation { matrix: scale }
+        }
+        // singular matrices can't be decomposed, blend them directly
+        _ => {
+            let mut data = from.matrix.data;
+            data.iter_mut()
+                .zip(to.matrix.data.iter())
+                .for_each(|(a, b)| *a += (b - *a) * t);
+            Transformation {
+                matrix: Matrix::new_from_vec(data),
+            }
+        }
+    }
+}
+
+/// splits `M = T * R * S` into translation, rotation and scale (polar decomposition).
+/// Mirrors (negative determinant) keep a proper rotation, the flip goes into the scale
+fn decompose(transformation: &Transformation) -> Option<Parts> {
+    let m = transformation.matrix;
+    let translation = Vector::new(m.get(0, 3), m.get(1, 3), m.get(2, 3));
+
+    // upper 3x3, without translation
+    let mut linear = m;
+    (0..3).for_each(|row| linear.write(row, 3, 0.0));
+
+    // average with inverse transpose until it converges to the rotation
+    let mut rotation = linear;
+    for _ in 0..100 {
+        let inverse_transpose = rotation.inverse()?.transpose();
+        let mut next = rotation;
+        next.data
+            .iter_mut()
+            .zip(inverse_transpose.data.iter())
+            .for_each(|(a, b)| *a = (*a + b) / 2.0);
+
+        let change: f64 = next
+            .data
+            .iter()
+            .zip(rotation.data.iter())
+            .map(|(a, b)| (a - b).abs())
+            .sum();
+        rotation = next;
+        if change < 1e-12 {
+            break;
+        }
+    }
+
+    // the orthogonal part of a mirror is a reflection, which a quaternion can't hold.
+    // -R is a rotation, and -R * -S is still M
+    if rotation.determinant() < 0.0 {
+        (0..3).for_each(|row| {
+            (0..3).for_each(|column| rotation.write(row, column, -rotation.get(row, column)))
+        });
+    }
+
+    let scale = rotation.transpose() * linear;
+    Some(Parts {
+        translation,
+        rotation: quaternion(&rotation),
+        scale,
+    })
+}
+
+/// rotation matrix to quaternion
+fn quaternion(r: &Matrix) -> Quaternion {
+    let (r00, r11, r22) = (r.get(0, 0), r.get(1, 1), r.get(2, 2));
+    let trace = r00 + r11 + r22;
+
+    if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        [
+            0.25 * s,
+            (r.get(2, 1) - r.get(1, 2)) / s,
+            (r.get(0, 2) - r.get(2, 0)) / s,
+            (r.get(1, 0) - r.get(0, 1)) / s,
+        ]
+    } else if r00 > r11 && r00 > r22 {
+        let s = (1.0 + r00 - r11 - r22).sqrt() * 2.0;
+        [
+            (r.get(2, 1) - r.get(1, 2)) / s,
+            0.25 * s,
+            (r.get(0, 1) + r.get(1, 0)) / s,
+            (r.get(0, 2) + r.get(2, 0)) / s,
+        ]
+    } else if r11 > r22 {
+        let s = (1.0 + r11 - r00 - r22).sqrt() * 2.0;
+        [
+            (r.get(0, 2) - r.get(2, 0)) / s,
+            (r.get(0, 1) + r.get(1, 0)) / s,
+            0.25 * s,
+            (r.get(1, 2) + r.get(2, 1)) / s,
+        ]
+    } else {
+        let s = (1.0 + r22 - r00 - r11).sqrt() * 2.0;
+        [
+            (r.get(1, 0) - r.get(0, 1)) / s,
+            (r.get(0, 2) + r.get(2, 0)) / s,
+            (r.get(1, 2) + r.get(2, 1)) / s,
+            0.25 * s,
+        ]
+    }
+}
+
+/// spherical interpolation, constant angular speed from `a` to `b`
+fn slerp(a: Quaternion, b: Quaternion, t: f64) -> Quaternion {
+    let mut b = b;
+    let mut cos = a.iter().zip(b.iter()).map(|(a, b)| a * b).sum::<f64>();
+
+    // take the short way around
+    if cos < 0.0 {
+        b.iter_mut().for_each(|x| *x = -*x);
+        cos = -cos;
+    }
+
+    let (wa, wb) = if cos > 0.9995 {
+        // nearly the same rotation, linear is fine (and avoids dividing by ~0)
+        (1.0 - t, t)
+    } else {
+        let theta = cos.acos();
+        let sin = theta.sin();
+        (((1.0 - t) * theta).sin() / sin, (t * theta).sin() / sin)
+    };
+
+    let mut q = [0.0; 4];
+    (0..4).for_each(|i| q[i] = wa * a[i] + wb * b[i]);
+    let length = q.iter().map(|x| x * x).sum::<f64>().sqrt();
+    q.iter_mut().for_each(|x| *x /= length);
+    q
+}
+
+/// quaternion to rotation matrix
+fn rotation_matrix(q: Quaternion) -> Matrix {
+    let [w, x, y, z] = q;
+    let (xx, yy, zz) = (x * x, y * y, z * z);
+    let (xy, xz, yz) = (x * y, x * z, y * z);
+    let (wx, wy, wz) = (w * x, w * y, w * z);
+
+    let vec = [
+        1.0 - 2.0 * (yy + zz),
+        2.0 * (xy - wz),
+        2.0 * (xz + wy),
+        0.0, //
+        2.0 * (xy + wz),
+        1.0 - 2.0 * (xx + zz),
+        2.0 * (yz - wx),
+        0.0, //
+        2.0 * (xz - wy),
+        2.0 * (yz + wx),
+        1.0 - 2.0 * (xx + yy),
+        0.0, //
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+    ];
+    Matrix::new_from_vec(vec)
+}
+
 impl Mul<Point> for Transformation {
     type Output = Point;
 
@@ -91,6 +91,11 @@ pub(super) fn object_normal_at(
     }
 }
 
+/// Same cylindrical mapping as `cylinder`
+pub(super) fn object_uv_at(object_point: Point) -> (f64, f64) {
+    super::cylinder::object_uv_at(object_point)
+}
+
 /// Returns a `Shape` with `shape_type` `cone`
 /// Equivelent to `Shape::new(transformation, material, ShapeType::cone, None)`
 pub fn new<'a>(
@@ -136,7 +141,7 @@ pub fn semi_default<'a>(min: f64, max: f64, closed: bool) -> Shape<'a> {
 /// a helper function
 /// checks to see if the `intersection` (`intersects_at`) is within the radius of 1
 /// (radius of the Cone) from the y axis
-fn check_cap(Ray { origin, direction }: &Ray, intersection: f64, radius: f64) -> bool {
+fn check_cap(Ray { origin, direction, .. }: &Ray, intersection: f64, radius: f64) -> bool {
     let x = origin.x + intersection * direction.x;
     let z = origin.z + intersection * direction.z;
 
@@ -56,6 +56,21 @@ pub(super) fn object_normal_at(_cube: &Shape, object_point: Point) -> Option<Vec
     })
 }
 
+/// Every face is mapped to the whole `[0, 1]` square, viewed from outside the cube
+pub(super) fn object_uv_at(object_point: Point) -> (f64, f64) {
+    let Point { x, y, z } = object_point;
+    let maxc = x.abs().max(y.abs()).max(z.abs());
+    let to_uv = |a: f64, b: f64| ((a + 1.0) / 2.0, (b + 1.0) / 2.0);
+
+    if (maxc - x.abs()).abs() < constants::EPSILON {
+        to_uv(-z * x.signum(), y)
+    } else if (maxc - y.abs()).abs() < constants::EPSILON {
+        to_uv(x, -z * y.signum())
+    } else {
+        to_uv(x * z.signum(), y)
+    }
+}
+
 fn check_axis(origin: f64, direction: f64) -> (f64, f64) {
     let tmin_numerator = -1.0 - origin;
     let tmax_numerator = 1.0 - origin;
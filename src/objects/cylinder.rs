@@ -1,4 +1,4 @@
-use std::f64::{INFINITY, NEG_INFINITY};
+use std::f64::{consts::PI, INFINITY, NEG_INFINITY};
 
 use crate::{
     constants,
@@ -78,6 +78,13 @@ pub(super) fn object_normal_at(
     }
 }
 
+/// Cylindrical mapping, `u` goes around the y axis and `v` repeats every unit along it
+pub(super) fn object_uv_at(object_point: Point) -> (f64, f64) {
+    let theta = object_point.x.atan2(object_point.z);
+    let u = 1.0 - (theta / (2.0 * PI) + 0.5);
+    (u, object_point.y.rem_euclid(1.0))
+}
+
 /// Returns a `Shape` with `shape_type` `cylinder`
 /// Equivelent to `Shape::new(transformation, material, ShapeType::Cylinder)`
 pub fn new<'a>(
@@ -122,7 +129,7 @@ pub fn semi_default<'a>(min: f64, max: f64, closed: bool) -> Shape<'a> {
 /// a helper function
 /// checks to see if the `intersection` (`intersects_at`) is within the radius of 1
 /// (radius of the cylinder) from the y axis
-fn check_cap(Ray { origin, direction }: &Ray, intersection: f64) -> bool {
+fn check_cap(Ray { origin, direction, .. }: &Ray, intersection: f64) -> bool {
     let x = origin.x + intersection * direction.x;
     let z = origin.z + intersection * direction.z;
 
@@ -1,6 +1,11 @@
 use crate::{
     constants,
-    math::{point::Point, ray::Ray, vector::Vector},
+    graphics::{color::Color, materials::Material},
+    math::{
+        point::Point,
+        ray::{Ray, RayKind},
+        vector::Vector,
+    },
 };
 
 use super::shape::Shape;
@@ -28,14 +33,23 @@ pub struct PreComputed<'a> {
     pub normalv: Vector,
     /// Reflected Ray Direction (assuming there is reflection!)
     pub reflectv: Vector,
-    /// Offsets towards the normal, prevents shadow 'acne'
+    /// Offsets towards the normal by `constants::EPSILON`, prevents shadow 'acne'.
+    /// Renders offset by `RenderSettings::shadow_bias` instead, see `World::shadow_point`
     pub over_point: Point,
-    /// Offsets below the normal, refactored ray originate here
+    /// Offsets below the normal by `constants::EPSILON`.
+    /// Refracted rays in renders start `RenderSettings::ray_bias` below instead
     pub under_point: Point,
     /// refractive index of the material being exited (n1)
     pub refractive_exited: f64,
     /// refractive index of the material being entered (n2)
     pub refractive_entered: f64,
+    /// time of the ray that hit, secondary rays are cast at the same time
+    pub time: f64,
+    /// wavelength of the ray that hit, see `Ray::wavelength`
+    pub wavelength: Option<f64>,
+    /// material of the object being exited, the ray travelled through it to the hit.
+    /// `None` when the ray came from outside every object
+    pub exited_material: Option<Material>,
 }
 
 impl<'a> Intersections<'a> {
@@ -114,7 +128,8 @@ impl<'a> Intersection<'a> {
         let inside: bool;
 
         // TODO: remove the unwrap
-        let (refractive_exited, refractive_entered) = refractive_index(self, xs.unwrap());
+        let (refractive_exited, refractive_entered, exited_material) =
+            refractive_index(self, xs.unwrap(), ray.wavelength);
 
         if normalv.dot_product(&eyev) < 0.0 {
             inside = true;
@@ -138,11 +153,69 @@ impl<'a> Intersection<'a> {
             under_point,
             refractive_exited,
             refractive_entered,
+            time: ray.time,
+            wavelength: ray.wavelength,
+            exited_material,
         })
     }
 }
 
 impl PreComputed<'_> {
+    /// reflected or refracted ray of `kind` from `origin`, at the same time and wavelength
+    pub fn secondary_ray(&self, origin: Point, direction: Vector, kind: RayKind) -> Ray {
+        Ray {
+            wavelength: self.wavelength,
+            kind,
+            ..Ray::new_at_time(origin, direction, self.time)
+        }
+    }
+
+    /// light left after travelling inside `exited_material` from the ray's origin
+    /// to the hit, see `Material::transmittance`. White outside every object
+    pub fn transmittance(&self) -> Color {
+        let distance = self.intersects_at * self.eyev.magnitude();
+        match self.exited_material {
+            Some(material) => material.transmittance(distance),
+            None => Color::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    /// direction of the refracted ray, `None` on total internal reflection
+    pub fn refracted_direction(&self) -> Option<Vector> {
+        let n_ratio = self.refractive_exited / self.refractive_entered;
+        let cos_i = self.eyev.dot_product(&self.normalv);
+        let sin2_t = n_ratio.powi(2) * (1.0 - cos_i.powi(2));
+
+        if sin2_t > 1.0 {
+            None
+        } else {
+            let cos_t = (1.0 - sin2_t).sqrt();
+            Some(self.normalv * (n_ratio * cos_i - cos_t) - self.eyev * n_ratio)
+        }
+    }
+
+    /// shares of the reflected and refracted light, `schlick` and its complement
+    /// when the material is both reflective and transparent, `(1.0, 1.0)` otherwise
+    pub fn fresnel(&self) -> (f64, f64) {
+        let material = self.object.material;
+        if material.reflective > 0.0 && material.transparency > 0.0 {
+            let reflectance = self.schlick();
+            (reflectance, 1.0 - reflectance)
+        } else {
+            (1.0, 1.0)
+        }
+    }
+
+    /// `reflective` and `transparency` of the material, weighted by `fresnel`
+    pub fn lobe_weights(&self) -> (f64, f64) {
+        let material = self.object.material;
+        let (reflected, refracted) = self.fresnel();
+        (
+            material.reflective * reflected,
+            material.transparency * refracted,
+        )
+    }
+
     /// Finds Fresnel Effect
     /// returns reflectance
     pub fn schlick(&self) -> f64 {
@@ -168,9 +241,15 @@ impl PreComputed<'_> {
     }
 }
 
-fn refractive_index(hit: &Intersection, xs: &Intersections) -> (f64, f64) {
+/// `(n1, n2, exited material)` at `hit`
+fn refractive_index(
+    hit: &Intersection,
+    xs: &Intersections,
+    wavelength: Option<f64>,
+) -> (f64, f64, Option<Material>) {
     let mut refractive_exited: f64 = 1.0;
     let mut refractive_entered: f64 = 1.0;
+    let mut exited_material = None;
 
     // record which objects have been encoutered (for refraction)
     let mut containers: Vec<Shape> = Vec::with_capacity(xs.list.len());
@@ -181,7 +260,8 @@ fn refractive_index(hit: &Intersection, xs: &Intersections) -> (f64, f64) {
 
         if is_hit {
             if let Some(last) = containers.last() {
-                refractive_exited = last.material.refractive_index;
+                refractive_exited = last.material.refractive_index_at(wavelength);
+                exited_material = Some(last.material);
             } else {
                 refractive_exited = 1.0
             }
@@ -198,12 +278,12 @@ fn refractive_index(hit: &Intersection, xs: &Intersections) -> (f64, f64) {
         }
         if is_hit {
             if let Some(last) = containers.last() {
-                refractive_entered = last.material.refractive_index;
+                refractive_entered = last.material.refractive_index_at(wavelength);
             } else {
                 refractive_entered = 1.0;
             }
             break;
         }
     }
-    (refractive_exited, refractive_entered)
+    (refractive_exited, refractive_entered, exited_material)
 }
@@ -41,6 +41,14 @@ pub(super) fn object_normal_at(_plane: &Shape, _object_point: Point) -> Option<V
     Some(Vector::new(0.0, 1.0, 0.0))
 }
 
+/// Planar mapping, repeats every unit along x and z
+pub(super) fn object_uv_at(object_point: Point) -> (f64, f64) {
+    (
+        object_point.x.rem_euclid(1.0),
+        object_point.z.rem_euclid(1.0),
+    )
+}
+
 /// Returns a `Shape` with `shape_type` `Plane`
 /// Equivelent to `Shape::new(transformation, material, ShapeType::Sphere)`
 pub fn new<'a>(transformation: Transformation, material: Material) -> Shape<'a> {
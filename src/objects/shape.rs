@@ -1,7 +1,7 @@
-use crate::graphics::{color::Color, materials::Material};
+use crate::graphics::{color::Color, materials::Material, volumes::Density};
 use crate::math::point::Point;
-use crate::math::ray::Ray;
-use crate::math::transformations::Transformation;
+use crate::math::ray::{Ray, RayKind};
+use crate::math::transformations::{Keyframe, Transformation};
 use crate::math::vector::Vector;
 use crate::objects::intersections::Intersections;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -23,6 +23,54 @@ pub struct Shape<'a> {
     pub shape_type: ShapeType,
     /// Allows `Shape`s to be `Group`ed together
     pub parent: Option<&'a Group<'a>>,
+    /// Animated `Shape`s replace `transformation` with the one at the ray's time,
+    /// interpolated between these keyframes (sorted by time)
+    pub motion: Option<&'a [Keyframe]>,
+    /// Volumes only (see `Material::medium`): how dense the medium is at each point
+    /// in *object-space*. `None` is the same everywhere
+    pub density: Option<&'a Density>,
+    /// which rays see the `Shape`, and whether it casts and receives shadows
+    pub visibility: Visibility,
+}
+
+/// Which rays see a `Shape`, everything is `true` by default.
+/// E.g. a fill card lighting up reflections but hidden from the camera
+#[derive(PartialEq, Eq, Copy, Debug, Clone)]
+pub struct Visibility {
+    /// blocks the light from other objects, and is hit by photons straight from the lights
+    pub casts_shadows: bool,
+    /// darkened by other objects' shadows
+    pub receives_shadows: bool,
+    /// seen by rays from the camera
+    pub camera: bool,
+    /// seen in reflections
+    pub reflection: bool,
+    /// seen through transparent objects
+    pub refraction: bool,
+}
+
+impl Default for Visibility {
+    fn default() -> Self {
+        Self {
+            casts_shadows: true,
+            receives_shadows: true,
+            camera: true,
+            reflection: true,
+            refraction: true,
+        }
+    }
+}
+
+impl Visibility {
+    /// whether rays of `kind` see the `Shape`
+    pub fn seen_by(&self, kind: RayKind) -> bool {
+        match kind {
+            RayKind::Camera => self.camera,
+            RayKind::Reflection => self.reflection,
+            RayKind::Refraction => self.refraction,
+            RayKind::Light => self.casts_shadows,
+        }
+    }
 }
 
 #[derive(PartialEq, Copy, Debug, Clone)]
@@ -57,11 +105,11 @@ impl<'a> Shape<'a> {
     /// `None` can also be used when finding `Intersections` is impossible
     /// such as not being able to convert from *world-space* to *object-space*
     pub fn intersects(&'a self, ray: &Ray) -> Option<Intersections<'a>> {
-        let transformation = self.transformation.inverse()?;
-        let ray = ray.transform(transformation);
+        let transformation = self.transformation_at(ray.time);
+        let ray = ray.transform(transformation.inverse()?);
 
         use ShapeType::*;
-        match self.shape_type {
+        let mut xs = match self.shape_type {
             Sphere => sphere::local_intersects(self, ray),
             Plane => plane::local_intersects(self, ray),
             Cube => cube::local_intersects(self, ray),
@@ -69,7 +117,22 @@ impl<'a> Shape<'a> {
                 cylinder::local_intersects(self, ray, min, max, closed)
             }
             Cone { min, max, closed } => cone::local_intersects(self, ray, min, max, closed),
+        }?;
+
+        // intersections remember where the object was, normals and patterns use it
+        if self.motion.is_some() {
+            xs.list
+                .iter_mut()
+                .for_each(|i| i.object.transformation = transformation);
         }
+        Some(xs)
+    }
+
+    /// `transformation` at `time`, differs from `transformation` only for animated `Shape`s
+    pub fn transformation_at(&self, time: f64) -> Transformation {
+        self.motion
+            .and_then(|keyframes| Transformation::at_time(keyframes, time))
+            .unwrap_or(self.transformation)
     }
 
     /// Finds the Normal at point, point must be in *world-coordinates*
@@ -102,6 +165,22 @@ impl<'a> Shape<'a> {
         Some(world_normal.normalize())
     }
 
+    /// Texture coordinates at point, point must be in *world-coordinates*.
+    /// Both are in `[0, 1]`, see `object_uv_at` of each shape for the mapping
+    /// `None` is used when converting from *world-space* to *object-space* is impossible
+    pub fn uv_at(&self, world_point: Point) -> Option<(f64, f64)> {
+        let object_point = self.transformation.inverse()? * world_point;
+
+        use ShapeType::*;
+        Some(match self.shape_type {
+            Sphere => sphere::object_uv_at(object_point),
+            Plane => plane::object_uv_at(object_point),
+            Cube => cube::object_uv_at(object_point),
+            Cylinder { .. } => cylinder::object_uv_at(object_point),
+            Cone { .. } => cone::object_uv_at(object_point),
+        })
+    }
+
     /// Finds the `Color` at point caused by patterns, point must be in *world-coordinates*
     /// Returns `Some(Color)` if there is a color pattern at that `world_point`
     /// `None` is used when finding `Color` is impossible
@@ -131,6 +210,9 @@ impl<'a> Shape<'a> {
             material,
             shape_type,
             parent,
+            motion: None,
+            density: None,
+            visibility: Visibility::default(),
         }
     }
 }
@@ -149,6 +231,9 @@ impl Default for Shape<'_> {
             material: Material::default(),
             shape_type: ShapeType::Sphere,
             parent: None,
+            motion: None,
+            density: None,
+            visibility: Visibility::default(),
         }
     }
 }
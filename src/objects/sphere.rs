@@ -1,3 +1,5 @@
+use std::f64::consts::PI;
+
 use crate::{
     graphics::materials::Material,
     math::{
@@ -43,6 +45,18 @@ pub(super) fn object_normal_at(_sphere: &Shape, object_point: Point) -> Option<V
     Some(object_normal)
 }
 
+/// Spherical mapping, `u` goes around the y axis and `v` from the bottom pole to the top
+pub(super) fn object_uv_at(object_point: Point) -> (f64, f64) {
+    let p = object_point;
+    let theta = p.x.atan2(p.z);
+    let radius = (p - point::ORIGIN).magnitude();
+    let phi = (p.y / radius).clamp(-1.0, 1.0).acos();
+
+    let u = 1.0 - (theta / (2.0 * PI) + 0.5);
+    let v = 1.0 - phi / PI;
+    (u, v)
+}
+
 /// Returns a `Shape` with `shape_type` `Sphere`
 /// Equivelent to `Shape::new(transformation, material, ShapeType::Sphere)`
 pub fn new<'a>(transformation: Transformation, material: Material) -> Shape<'a> {
@@ -1,10 +1,19 @@
 use crate::{
     graphics::{
         color::{self, Color},
-        lights::PointLight,
+        lights::Light,
         materials::Material,
+        media::Medium,
+        photon_map::PhotonMap,
+        settings::RenderSettings,
+    },
+    math::{
+        point::Point,
+        ray::{Ray, RayKind},
+        sampling::Pcg,
+        transformations::Transformation,
+        vector::Vector,
     },
-    math::{point::Point, ray::Ray, transformations::Transformation},
     objects::shape,
 };
 
@@ -13,16 +22,77 @@ use super::{
     shape::Shape,
 };
 
-/// A world of `objects` (now only `Spheres`!) and `Pointlight`
+/// Contributions to the color at an intersection, see `World::shade_hit_components`
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Shading {
+    /// direct light on the surface (ambient, diffuse and specular), plus caustics
+    pub surface: Color,
+    /// reflection, already weighted by `reflective` (and Fresnel)
+    pub reflected: Color,
+    /// refraction, already weighted by `transparency` (and Fresnel)
+    pub refracted: Color,
+    /// share of the light reaching the surface, averaged over the lights.
+    /// See `World::light_transmittance`
+    pub light_attenuation: Color,
+}
+
+impl Shading {
+    /// final color, eq to `World::shade_hit`
+    pub fn total(&self) -> Color {
+        self.surface + self.reflected + self.refracted
+    }
+}
+
+/// Part of a ray inside a volume, see `World::intersect_media`
+#[derive(Debug, Clone, Copy)]
+pub struct MediumSpan<'s> {
+    /// `t` where the ray enters, negative if it starts inside
+    pub enter: f64,
+    /// `t` where the ray leaves
+    pub exit: f64,
+    pub medium: Medium,
+    /// the volume
+    pub object: &'s Shape<'s>,
+}
+
+impl MediumSpan<'_> {
+    /// `object.density` at `point` (*world-coordinates*), `1.0` without one
+    pub fn density_at(&self, point: Point, time: f64) -> f64 {
+        match self.object.density {
+            Some(density) => match self.object.transformation_at(time).inverse() {
+                Some(inverse) => density.at(inverse * point),
+                None => 0.0,
+            },
+            None => 1.0,
+        }
+    }
+
+    /// largest `density_at` anywhere
+    pub fn max_density(&self) -> f64 {
+        self.object.density.map_or(1.0, |density| density.max())
+    }
+}
+
+/// A world of `objects` (now only `Spheres`!) and `Light`s
 #[derive(PartialEq, Debug, Clone)]
 pub struct World<'a> {
     pub objects: Vec<Shape<'a>>,
-    pub light: Option<PointLight>,
+    /// every light adds to the shading, each with its own shadows.
+    /// Without lights only emissive effects (none yet) and the background show
+    pub lights: Vec<Light>,
+    /// recursion depth, biases, background..., see `RenderSettings`
+    pub settings: RenderSettings,
+    /// caustics, see `PhotonMap::build`. Defaults to `None`.
+    /// The light through transparent objects then only arrives as photons,
+    /// they cast full shadows (see `light_transmittance`)
+    pub photon_map: Option<PhotonMap>,
+    /// fog filling the whole scene, see `Medium`. Defaults to `None`
+    pub atmosphere: Option<Medium>,
 }
 
 impl Default for World<'_> {
     fn default() -> Self {
-        let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let light = Light::new(Point::new(-10.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
         let mut s1 = Shape::default();
         s1.material.color = Color::new(0.8, 1.0, 0.6);
         s1.material.diffuse = 0.7;
@@ -31,7 +101,10 @@ impl Default for World<'_> {
         let s2 = shape::sphere::new(Transformation::scaling(0.5, 0.5, 0.5), Material::default());
         World {
             objects: vec![s1, s2],
-            light: Some(light),
+            lights: vec![light],
+            settings: RenderSettings::default(),
+            photon_map: None,
+            atmosphere: None,
         }
     }
 }
@@ -41,86 +114,453 @@ impl World<'_> {
     pub fn new() -> Self {
         World {
             objects: vec![],
-            light: None,
+            lights: vec![],
+            settings: RenderSettings::default(),
+            photon_map: None,
+            atmosphere: None,
         }
     }
     /// intersects every object in the world with the ray, returns sorted Intersections.
+    /// Volumes (objects with a `Material::medium`) have no surface and are left out,
+    /// see `intersect_media`, and so are objects hidden from the ray's `kind` (`Shape::visibility`)
     pub fn intersect(&self, ray: Ray) -> Intersections {
         let mut intersections = Intersections { list: vec![] };
 
-        self.objects.iter().for_each(|object| {
-            let mut i = object
-                .intersects(&ray)
-                // empty
-                .unwrap_or(Intersections { list: vec![] });
-            intersections.list.append(&mut i.list);
-        });
+        self.surfaces()
+            .filter(|object| object.visibility.seen_by(ray.kind))
+            .for_each(|object| {
+                let mut i = object
+                    .intersects(&ray)
+                    // empty
+                    .unwrap_or(Intersections { list: vec![] });
+                intersections.list.append(&mut i.list);
+            });
         intersections
             .list
             .sort_by(|a, b| a.intersects_at.partial_cmp(&b.intersects_at).unwrap());
         intersections
     }
 
+    /// objects with a surface, everything but volumes
+    fn surfaces(&self) -> impl Iterator<Item = &Shape<'_>> {
+        self.objects
+            .iter()
+            .filter(|object| object.material.medium.is_none())
+    }
+
+    /// Spans of `ray` inside volumes (objects with a `Material::medium`).
+    /// Volumes need to be closed shapes, a ray entering one has to leave it
+    pub fn intersect_media(&self, ray: Ray) -> Vec<MediumSpan<'_>> {
+        let mut spans = vec![];
+        self.objects.iter().for_each(|object| {
+            let medium = match object.material.medium {
+                Some(medium) => medium,
+                None => return,
+            };
+            if let Some(xs) = object.intersects(&ray) {
+                let mut ts: Vec<f64> = xs.list.iter().map(|i| i.intersects_at).collect();
+                ts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                ts.chunks_exact(2).for_each(|pair| {
+                    spans.push(MediumSpan {
+                        enter: pair[0],
+                        exit: pair[1],
+                        medium,
+                        object,
+                    })
+                });
+            }
+        });
+        spans
+    }
+
+    /// whether there is an `atmosphere` or any object filled with a medium
+    fn has_media(&self) -> bool {
+        self.atmosphere.is_some()
+            || self
+                .objects
+                .iter()
+                .any(|object| object.material.medium.is_some())
+    }
+
+    /// Participating media along `ray` before `t_max` (the hit, `f64::INFINITY` on a miss),
+    /// returns `(transmittance, scattered)`: the share of the light from `t_max`
+    /// that gets through, and the light from `lights` scattered towards the ray's origin.
+    /// Single scattering. Homogeneous media are sampled at `settings.volume_steps` points
+    /// per span, heterogeneous ones (`Shape::density`) with delta tracking, which is noisy,
+    /// the ray either scatters once or passes through
+    pub fn participate(&self, ray: Ray, t_max: f64) -> (Color, Color) {
+        let white = Color::new(1.0, 1.0, 1.0);
+        if !self.has_media() {
+            return (white, color::BLACK);
+        }
+        let spans = self.intersect_media(ray);
+        if self.atmosphere.is_none() && spans.is_empty() {
+            return (white, color::BLACK);
+        }
+        let speed = ray.direction.magnitude();
+        let mut rng = Pcg::for_ray(self.settings.seed, &ray);
+
+        // on a miss the atmosphere goes on forever, stop once it is (nearly) opaque
+        let volumes_end = spans.iter().fold(0.0, |end: f64, span| end.max(span.exit));
+        let t_end = if t_max.is_finite() {
+            t_max
+        } else {
+            match self.atmosphere {
+                Some(fog) if fog.extinction() > 0.0 => {
+                    volumes_end.max(1000.0_f64.ln() / fog.extinction() / speed)
+                }
+                _ => volumes_end,
+            }
+        };
+
+        // the media don't change between these
+        let mut bounds = vec![0.0, t_end];
+        spans.iter().for_each(|span| {
+            bounds.push(span.enter.clamp(0.0, t_end));
+            bounds.push(span.exit.clamp(0.0, t_end));
+        });
+        bounds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        bounds.dedup();
+
+        let mut transmittance = 1.0;
+        let mut scattered = color::BLACK;
+        for window in bounds.windows(2) {
+            let (start, end) = (window[0], window[1]);
+            let middle = (start + end) / 2.0;
+            let inside: Vec<&MediumSpan> = spans
+                .iter()
+                .filter(|span| span.enter <= middle && middle <= span.exit)
+                .collect();
+            if self.atmosphere.is_none() && inside.is_empty() {
+                continue;
+            }
+            // light scattered at `point` towards the ray's origin, per unit distance,
+            // `density` is `(medium, density)` of every medium there
+            let in_scattered = |point: Point, density: &[(Medium, f64)], rng: &mut Pcg| {
+                self.lights.iter().fold(color::BLACK, |sum, light| {
+                    let to_light = light.direction_from(point);
+                    let cos_theta = ray.direction.normalize().dot_product(&to_light);
+                    let arriving = self.light_through_media(light, point, ray.time, rng);
+                    density.iter().fold(sum, |sum, (medium, density)| {
+                        sum + medium.color
+                            * arriving
+                            * (medium.scattering * density * medium.phase(cos_theta))
+                    })
+                })
+            };
+            let density_at = |point: Point| -> Vec<(Medium, f64)> {
+                self.atmosphere
+                    .map(|fog| (fog, 1.0))
+                    .into_iter()
+                    .chain(
+                        inside
+                            .iter()
+                            .map(|span| (span.medium, span.density_at(point, ray.time))),
+                    )
+                    .collect()
+            };
+            let extinction_of = |density: &[(Medium, f64)]| -> f64 {
+                density.iter().map(|(m, d)| m.extinction() * d).sum()
+            };
+
+            if inside.iter().all(|span| span.object.density.is_none()) {
+                let density = density_at(ray.position(middle));
+                let extinction = extinction_of(&density);
+                let steps = self.settings.volume_steps.max(1);
+                let step = (end - start) / steps as f64;
+                for i in 0..steps {
+                    let t = start + (i as f64 + 0.5) * step;
+                    let reaching = transmittance * (-extinction * (t - start) * speed).exp();
+                    scattered = scattered
+                        + in_scattered(ray.position(t), &density, &mut rng)
+                            * (reaching * step * speed);
+                }
+                transmittance *= (-extinction * (end - start) * speed).exp();
+            } else {
+                // delta tracking: tentative collisions as if the medium was everywhere
+                // as dense as it gets, a collision is real as often as the medium is that dense
+                let majorant: f64 = self.atmosphere.map_or(0.0, |fog| fog.extinction())
+                    + inside
+                        .iter()
+                        .map(|span| span.medium.extinction() * span.max_density())
+                        .sum::<f64>();
+                if majorant <= 0.0 {
+                    continue;
+                }
+                let mut t = start;
+                loop {
+                    t -= (1.0 - rng.next_f64()).ln() / (majorant * speed);
+                    if t >= end {
+                        break;
+                    }
+                    let point = ray.position(t);
+                    let density = density_at(point);
+                    let extinction = extinction_of(&density);
+                    if rng.next_f64() * majorant < extinction {
+                        // scattered towards the origin or absorbed, nothing behind gets through
+                        scattered = scattered
+                            + in_scattered(point, &density, &mut rng)
+                                * (transmittance / extinction);
+                        return (color::BLACK, scattered);
+                    }
+                }
+            }
+        }
+        (white * transmittance, scattered)
+    }
+
+    /// light from `light` arriving at `point`, through the media on the way.
+    /// Black in the shadow of an opaque surface
+    fn light_through_media(&self, light: &Light, point: Point, time: f64, rng: &mut Pcg) -> Color {
+        let ray = Ray::new_at_time(point, light.direction_from(point), time);
+        light.intensity_at(point)
+            * self.light_transmittance(light, point, time)
+            * self.transmittance_along(ray, light.distance_from(point), rng)
+    }
+
+    /// share of the light that gets through the media between `from` and `to`.
+    /// An estimate through heterogeneous volumes, see `participate`
+    pub fn media_transmittance(&self, from: Point, to: Point, time: f64) -> f64 {
+        // `to` is at t = 1
+        let ray = Ray::new_at_time(from, to - from, time);
+        let mut rng = Pcg::for_ray(self.settings.seed, &ray);
+        self.transmittance_along(ray, 1.0, &mut rng)
+    }
+
+    /// transmittance along `ray` up to `t_max`,
+    /// exact in homogeneous media and ratio tracked in heterogeneous volumes.
+    /// The `atmosphere` only dims a finite `t_max`, sunlight (see `LightType::Directional`)
+    /// reaches the whole scene
+    fn transmittance_along(&self, ray: Ray, t_max: f64, rng: &mut Pcg) -> f64 {
+        if !self.has_media() {
+            return 1.0;
+        }
+        let speed = ray.direction.magnitude();
+        let mut optical_depth = match self.atmosphere {
+            Some(fog) if t_max.is_finite() => fog.extinction() * t_max * speed,
+            _ => 0.0,
+        };
+        let mut transmittance = 1.0;
+        self.intersect_media(ray).iter().for_each(|span| {
+            let (start, end) = (span.enter.max(0.0), span.exit.min(t_max));
+            if start >= end {
+                return;
+            }
+            let extinction = span.medium.extinction();
+            if span.object.density.is_none() {
+                optical_depth += extinction * (end - start) * speed;
+                return;
+            }
+            // ratio tracking: tentative collisions as in delta tracking,
+            // each lets through the share of the majorant that isn't there
+            let majorant = extinction * span.max_density();
+            if majorant <= 0.0 {
+                return;
+            }
+            let mut t = start;
+            loop {
+                t -= (1.0 - rng.next_f64()).ln() / (majorant * speed);
+                if t >= end {
+                    break;
+                }
+                let density = span.density_at(ray.position(t), ray.time);
+                transmittance *= 1.0 - extinction * density / majorant;
+            }
+        });
+        transmittance * (-optical_depth).exp()
+    }
+
     /// intersects with the world given the ray and then return color at resulting intersection
     /// `remaining` is the number of recurisive calls left. this is to prevent infinite recursion
+    /// (`settings.max_depth` when rendering). Misses are `settings.background`.
+    /// Fog and smoke on the way dim the color and add the light they scatter, see `participate`
     pub fn color_at(&self, ray: Ray, remaining: isize) -> Color {
         let is = self.intersect(ray);
-        if let Some(hit) = is.hit() {
+        let (t_max, color) = if let Some(hit) = is.hit() {
             let comp = hit.prepare_computations(ray, Some(&is)).unwrap();
-            self.shade_hit(&comp, remaining - 1)
+            // tinted by the glass it went through
+            let color = self.shade_hit(&comp, remaining - 1) * comp.transmittance();
+            (hit.intersects_at, color)
         } else {
-            color::BLACK
-        }
+            (f64::INFINITY, self.settings.background)
+        };
+        let (transmittance, scattered) = self.participate(ray, t_max);
+        color * transmittance + scattered
     }
-    /// Returns true if there is a shadow
+    /// Returns true if there is a shadow, no light reaches `point`
     pub fn is_shadowed(&self, point: Point) -> bool {
-        if let Some(light) = self.light {
-            let v = light.position - point;
-            let distance = v.magnitude();
-            let direction = v.normalize();
-
-            let ray = Ray::new(point, direction);
-            let intersections = self.intersect(ray);
-            match intersections.hit() {
-                Some(hit) => hit.intersects_at < distance,
-                None => false,
+        self.is_shadowed_at_time(point, 0.0)
+    }
+
+    /// Same as `is_shadowed`, with animated objects where they are at `time`.
+    /// Only opaque objects block the light completely, see `light_transmittance`.
+    /// Without lights there are no shadows
+    pub fn is_shadowed_at_time(&self, point: Point, time: f64) -> bool {
+        !self.lights.is_empty()
+            && self
+                .lights
+                .iter()
+                .all(|light| self.light_transmittance(light, point, time) == color::BLACK)
+    }
+
+    /// Share of `light` reaching `point` (at `time`), per channel. Black behind opaque
+    /// objects, transparent ones let `transparency` through at every surface
+    /// and tint the light with their `absorption` inside. Shadow rays go straight
+    /// to the light, they are not bent by refraction.
+    /// Objects that don't `Visibility::casts_shadows` are skipped.
+    /// Area lights average the shadow rays to `Light::samples` points on them,
+    /// partly hidden lights give the penumbra. Directional lights are blocked by
+    /// anything in the opposite of their direction
+    pub fn light_transmittance(&self, light: &Light, point: Point, time: f64) -> Color {
+        self.transmittance_to_light(light, point, time, false)
+    }
+
+    /// `light_transmittance`, with transparent objects opaque when `caustics`
+    /// already carry the light through them, it would be counted twice
+    fn transmittance_to_light(
+        &self,
+        light: &Light,
+        point: Point,
+        time: f64,
+        caustics: bool,
+    ) -> Color {
+        if !light.is_area() {
+            let (direction, distance) = (light.direction_from(point), light.distance_from(point));
+            return self.transmittance_towards(point, direction, distance, time, caustics);
+        }
+        // same point same samples, no noise between renders
+        let ray = Ray::new_at_time(point, light.position - point, time);
+        let mut rng = Pcg::for_ray(self.settings.seed, &ray);
+        let targets = light.sample_points(&mut rng);
+
+        targets.iter().fold(color::BLACK, |sum, &target| {
+            let v = target - point;
+            sum + self.transmittance_towards(point, v.normalize(), v.magnitude(), time, caustics)
+        }) * (1.0 / targets.len() as f64)
+    }
+
+    /// share of the light coming from `distance` away along (normalized) `direction`
+    /// that reaches `point`, see `transmittance_to_light`
+    fn transmittance_towards(
+        &self,
+        point: Point,
+        direction: Vector,
+        distance: f64,
+        time: f64,
+        caustics: bool,
+    ) -> Color {
+        let ray = Ray::new_at_time(point, direction, time);
+
+        let mut transmittance = color::WHITE;
+        for object in self
+            .surfaces()
+            .filter(|object| object.visibility.casts_shadows)
+        {
+            let mut ts: Vec<f64> = match object.intersects(&ray) {
+                Some(xs) => xs.list.iter().map(|i| i.intersects_at).collect(),
+                None => continue,
+            };
+            ts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let crossed = ts.iter().filter(|&&t| t >= 0.0 && t < distance).count();
+            if crossed == 0 {
+                continue;
             }
-        } else {
-            false // no light = no shadow
+
+            let material = object.material;
+            if material.transparency <= 0.0 || caustics {
+                return color::BLACK;
+            }
+            // distance travelled inside, between the point and the light
+            let inside: f64 = ts
+                .chunks_exact(2)
+                .map(|pair| (pair[1].min(distance) - pair[0].max(0.0)).max(0.0))
+                .sum();
+            transmittance = transmittance
+                * material.transmittance(inside)
+                * material.transparency.powi(crossed as i32);
         }
+        transmittance
     }
 
     /// calculates the the color at intersection (from `PreComputed`)
     /// `remaining` is the number of recurisive calls left. this is to prevent infinite recursion
     pub fn shade_hit(&self, comps: &PreComputed, remaining: isize) -> Color {
-        let shadowed = self.is_shadowed(comps.over_point);
-
-        // color from surface
-        let surface = comps.object.material.lighting(
-            comps.object,
-            self.light.expect("no light!"),
-            comps.over_point,
-            comps.eyev,
-            comps.normalv,
-            shadowed,
+        self.shade_hit_components(comps, remaining).total()
+    }
+
+    /// same as `shade_hit`, but keeps the contributions apart (render passes)
+    pub fn shade_hit_components(&self, comps: &PreComputed, remaining: isize) -> Shading {
+        // color from surface, every light with its own shadow
+        let (surface, attenuation) = self.lights.iter().fold(
+            (color::BLACK, color::BLACK),
+            |(surface, attenuation), light| {
+                let light_attenuation =
+                    self.surface_light_with(light, comps, self.photon_map.is_some());
+                let color = comps.object.material.lighting(
+                    comps.object,
+                    *light,
+                    self.shadow_point(comps),
+                    comps.eyev,
+                    comps.normalv,
+                    light_attenuation,
+                );
+                (surface + color, attenuation + light_attenuation)
+            },
         );
+        let light_attenuation = if self.lights.is_empty() {
+            color::WHITE
+        } else {
+            attenuation * (1.0 / self.lights.len() as f64)
+        };
+        // focused light from the photon map
+        let surface = match &self.photon_map {
+            Some(map) => surface + self.caustics(map, comps),
+            None => surface,
+        };
         // color from reflection
         let reflected = self.reflected_color(comps, remaining - 1);
         let refracted = self.refracted_color(comps, remaining - 1);
 
+        // if both reflective and transparenct. use schlick formula to get Fresnel effect
+        let (reflectance, transmittance) = comps.fresnel();
+        let (reflected, refracted) = (reflected * reflectance, refracted * transmittance);
+
+        Shading {
+            surface,
+            reflected,
+            refracted,
+            light_attenuation,
+        }
+    }
+
+    /// diffuse light from the caustic photons around the hit
+    fn caustics(&self, map: &PhotonMap, comps: &PreComputed) -> Color {
         let material = comps.object.material;
+        let albedo = material.color_at(&comps.object, comps.point);
+        albedo * map.irradiance(comps.point, comps.normalv) * material.diffuse
+    }
 
-        // if both reflective and transparenct. use schlick formula to get Fresnel effect
-        if material.reflective > 0.0 && material.transparency > 0.0 {
-            let reflectance = comps.schlick();
-            surface + reflected * reflectance + //.
-		/*. */refracted * (1.0 - reflectance)
+    /// share of `light` reaching the hit, `light_transmittance` unless the object
+    /// doesn't `Visibility::receives_shadows`
+    pub fn surface_light(&self, light: &Light, comps: &PreComputed) -> Color {
+        self.surface_light_with(light, comps, false)
+    }
+
+    /// `surface_light`, through transparent objects only without `caustics`,
+    /// see `transmittance_to_light`
+    fn surface_light_with(&self, light: &Light, comps: &PreComputed, caustics: bool) -> Color {
+        if comps.object.visibility.receives_shadows {
+            self.transmittance_to_light(light, self.shadow_point(comps), comps.time, caustics)
         } else {
-            // final color
-            surface + reflected + refracted
+            color::WHITE
         }
     }
 
+    /// where rays towards the light start, `settings.shadow_bias` above the surface
+    pub fn shadow_point(&self, comps: &PreComputed) -> Point {
+        comps.point + comps.normalv * self.settings.shadow_bias
+    }
+
     /// color of reflected ray
     /// `remaining` is the number of recurisive calls left. this is to prevent infinite recursion
     /// if `remaining` is zero, the function will return `color::BLACK`
@@ -132,7 +572,8 @@ impl World<'_> {
         {
             color::BLACK
         } else {
-            let reflect_ray = Ray::new(comps.over_point, comps.reflectv);
+            let over_point = comps.point + comps.normalv * self.settings.ray_bias;
+            let reflect_ray = comps.secondary_ray(over_point, comps.reflectv, RayKind::Reflection);
             let color = self.color_at(reflect_ray, remaining - 1);
 
             // "dilute" the color with reflective
@@ -144,30 +585,19 @@ impl World<'_> {
     /// `remaining` is the number of recurisive calls left. this is to prevent infinite recursion
     /// if `remaining` is zero, the function will return `color::BLACK`
     pub fn refracted_color(&self, comps: &PreComputed, remaining: isize) -> Color {
-        let n1 = comps.refractive_exited;
-        let n2 = comps.refractive_entered;
-
-        // ratio of first index of refraction to the second.
-        let n_ratio = n1 / n2;
-        let cos_i = comps.eyev.dot_product(&comps.normalv);
-        let sin2_t = n_ratio.powi(2) * (1.0 - cos_i.powi(2));
-
         // reflection to begin with
-        if comps.object.material.transparency == 0.0
-	    // end recurisive reflection
-	    || sin2_t > 1.0
-        {
-            color::BLACK
-        } else {
-            // finding refracted ray
-            let cos_t = (1.0 - sin2_t).sqrt();
-            let direction = comps.normalv * (n_ratio * cos_i - cos_t) - //.
-		comps.eyev * n_ratio;
-
-            // create refracted ray
-            let refract_ray = Ray::new(comps.under_point, direction);
-            self.color_at(refract_ray, remaining - 1) * //.
-		comps.object.material.transparency
+        if comps.object.material.transparency == 0.0 {
+            return color::BLACK;
+        }
+        // finding refracted ray, none on total internal reflection
+        match comps.refracted_direction() {
+            Some(direction) => {
+                // create refracted ray
+                let under_point = comps.point - comps.normalv * self.settings.ray_bias;
+                let refract_ray = comps.secondary_ray(under_point, direction, RayKind::Refraction);
+                self.color_at(refract_ray, remaining - 1) * comps.object.material.transparency
+            }
+            None => color::BLACK,
         }
     }
 }
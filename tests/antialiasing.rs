@@ -0,0 +1,117 @@
+use raytracer::{
+    graphics::{
+        antialiasing::{Adaptive, Filter, SampleCounts, SamplePattern},
+        color::{self, Color},
+    },
+    math::sampling::Pcg,
+};
+
+#[test]
+fn grid_with_one_sample_is_pixel_center() {
+    let mut rng = Pcg::new(0, 0);
+    let positions = SamplePattern::Grid.positions(1, &mut rng);
+
+    assert_eq!(positions, vec![(0.5, 0.5)]);
+}
+
+#[test]
+fn grid_is_rounded_up_to_perfect_square() {
+    let mut rng = Pcg::new(0, 0);
+    let positions = SamplePattern::Grid.positions(3, &mut rng);
+
+    assert_eq!(
+        positions,
+        vec![(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)]
+    );
+}
+
+#[test]
+fn jittered_samples_stay_in_their_cell() {
+    let mut rng = Pcg::new(0, 0);
+    let positions = SamplePattern::Jittered.positions(16, &mut rng);
+
+    assert_eq!(positions.len(), 16);
+    positions.iter().enumerate().for_each(|(i, (u, v))| {
+        let (cell_u, cell_v) = ((i % 4) as f64 * 0.25, (i / 4) as f64 * 0.25);
+        assert!(*u >= cell_u && *u < cell_u + 0.25);
+        assert!(*v >= cell_v && *v < cell_v + 0.25);
+    });
+}
+
+#[test]
+fn halton_samples_are_inside_the_pixel() {
+    let mut rng = Pcg::new(0, 0);
+    let positions = SamplePattern::Halton.positions(8, &mut rng);
+
+    assert_eq!(positions.len(), 8);
+    assert!(positions
+        .iter()
+        .all(|(u, v)| (0.0..1.0).contains(u) && (0.0..1.0).contains(v)));
+}
+
+#[test]
+fn filters_are_zero_outside_their_radius() {
+    for filter in [
+        Filter::Box,
+        Filter::Tent,
+        Filter::Gaussian,
+        Filter::Mitchell,
+    ] {
+        let r = filter.radius();
+        assert!(filter.weight(0.0, 0.0) > 0.0);
+        assert_eq!(filter.weight(r + 0.01, 0.0), 0.0);
+        assert_eq!(filter.weight(0.0, r + 0.01), 0.0);
+    }
+}
+
+#[test]
+fn tent_filter_falls_off_linearly() {
+    assert_eq!(Filter::Tent.weight(0.0, 0.0), 1.0);
+    assert_eq!(Filter::Tent.weight(0.5, 0.0), 0.5);
+    assert_eq!(Filter::Tent.weight(0.5, 0.5), 0.25);
+}
+
+#[test]
+fn mitchell_filter_has_negative_lobes() {
+    assert!(Filter::Mitchell.weight(1.5, 0.0) < 0.0);
+}
+
+#[test]
+fn sample_count_matches_positions() {
+    let mut rng = Pcg::new(0, 0);
+    for pattern in [
+        SamplePattern::Grid,
+        SamplePattern::Jittered,
+        SamplePattern::Halton,
+    ] {
+        for samples in 1..10 {
+            let positions = pattern.positions(samples, &mut rng);
+            assert_eq!(pattern.count(samples), positions.len());
+        }
+    }
+}
+
+#[test]
+fn adaptive_detects_contrast() {
+    let adaptive = Adaptive {
+        threshold: 0.1,
+        max_depth: 2,
+    };
+
+    assert!(!adaptive.differ(&[color::WHITE, Color::new(0.95, 1.0, 0.92)]));
+    assert!(adaptive.differ(&[color::WHITE, Color::new(1.0, 0.8, 1.0)]));
+}
+
+#[test]
+fn sample_counts_as_canvas() {
+    let counts = SampleCounts {
+        width: 3,
+        height: 1,
+        counts: vec![5, 13, 21],
+    };
+    let canvas = counts.to_canvas();
+
+    assert_eq!(canvas.clone().get(0, 0), color::BLACK);
+    assert_eq!(canvas.clone().get(1, 0), Color::new(0.5, 0.5, 0.5));
+    assert_eq!(canvas.get(2, 0), color::WHITE);
+}
@@ -0,0 +1,127 @@
+use std::f64::consts::FRAC_PI_2;
+
+use raytracer::{
+    graphics::{
+        aov::Aov,
+        camera::Camera,
+        color::Color,
+        integrators::{Integrators, PathTracer},
+        lights::Light,
+    },
+    math::{point::Point, transformations::Transformation, vector::Vector},
+    objects::{intersections::Intersection, world::World},
+    testing::Testing,
+};
+
+fn camera() -> Camera {
+    let mut c = Camera::new(11, 11, FRAC_PI_2);
+    c.transform = Transformation::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+    c
+}
+
+#[test]
+fn shading_components_add_up_to_shade_hit() {
+    let w = World::default();
+    let ray =
+        raytracer::math::ray::Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    let i = Intersection::new(4.0, w.objects[0]);
+    let comps = i
+        .prepare_computations(ray, Some(&i.clone().agregate(i.clone())))
+        .unwrap();
+
+    let shading = w.shade_hit_components(&comps, 5);
+
+    assert_eq!(shading.total(), w.shade_hit(&comps, 5));
+    assert_eq!(shading.light_attenuation, Color::new(1.0, 1.0, 1.0));
+    assert_eq!(shading.reflected, Color::new(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn beauty_pass_matches_render() {
+    let w = World::default();
+    let c = camera();
+
+    let passes = c.render_aovs(w.clone(), &[Aov::Beauty]);
+
+    assert_eq!(passes.get(Aov::Beauty).unwrap().grid, c.render(w).grid);
+}
+
+#[test]
+fn passes_are_named() {
+    let passes = camera().render_aovs(World::default(), &[Aov::Depth, Aov::ObjectId]);
+
+    assert!(passes.get_by_name("depth").is_some());
+    assert!(passes.get_by_name("object_id").is_some());
+    assert!(passes.get(Aov::Beauty).is_none());
+    assert_eq!(passes.passes.len(), 2);
+}
+
+#[test]
+fn geometry_passes_at_the_center_of_the_sphere() {
+    let passes = camera().render_aovs(World::default(), &Aov::ALL);
+    let at = |aov: Aov| passes.get(aov).unwrap().clone().get(5, 5);
+
+    Testing::assert_nearly_eq(at(Aov::Depth), Color::new(4.0, 4.0, 4.0));
+    Testing::assert_nearly_eq(at(Aov::Normal), Color::new(0.0, 0.0, -1.0));
+    assert_eq!(at(Aov::Albedo), Color::new(0.8, 1.0, 0.6));
+    assert_eq!(at(Aov::Shadow), Color::new(0.0, 0.0, 0.0));
+    assert_eq!(at(Aov::Reflection), Color::new(0.0, 0.0, 0.0));
+    assert_eq!(at(Aov::Surface), at(Aov::Beauty));
+    // background
+    assert_eq!(
+        passes.get(Aov::Depth).unwrap().clone().get(0, 0),
+        Color::new(0.0, 0.0, 0.0)
+    );
+}
+
+#[test]
+fn reflection_pass() {
+    let mut w = World::default();
+    w.objects[0].material.reflective = 0.5;
+    // room around everything, for the reflection to show
+    let mut room = w.objects[1];
+    room.uid += 1;
+    room.transformation = Transformation::scaling(20.0, 20.0, 20.0);
+    w.objects.push(room);
+    let passes = camera().render_aovs(w, &[Aov::Reflection, Aov::Beauty, Aov::Surface]);
+
+    let reflection = passes.get(Aov::Reflection).unwrap().clone().get(5, 5);
+    let beauty = passes.get(Aov::Beauty).unwrap().clone().get(5, 5);
+    let surface = passes.get(Aov::Surface).unwrap().clone().get(5, 5);
+
+    assert_ne!(reflection, Color::new(0.0, 0.0, 0.0));
+    Testing::assert_nearly_eq(beauty, surface + reflection);
+}
+
+#[test]
+fn beauty_pass_uses_the_settings_integrator() {
+    let mut w = World::default();
+    let c = camera();
+    w.settings.integrator = Integrators::PathTracer(PathTracer::default());
+
+    let passes = c.render_aovs(w.clone(), &[Aov::Beauty, Aov::Surface]);
+
+    assert_eq!(passes.get(Aov::Beauty).unwrap().grid, c.render(w).grid);
+}
+
+#[test]
+fn shadow_pass() {
+    let w = World {
+        // behind the sphere
+        lights: vec![Light::new(
+            Point::new(0.0, 0.0, 10.0),
+            Color::new(1.0, 1.0, 1.0),
+        )],
+        ..World::default()
+    };
+
+    let passes = camera().render_aovs(w, &[Aov::Shadow]);
+
+    let shadow = passes.get(Aov::Shadow).unwrap().clone();
+    assert_eq!(shadow.clone().get(5, 5), Color::new(1.0, 1.0, 1.0));
+    assert_eq!(shadow.get(0, 0), Color::new(0.0, 0.0, 0.0));
+}
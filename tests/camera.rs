@@ -1,8 +1,18 @@
 use std::{f64::consts, f64::consts::FRAC_PI_2};
 
 use raytracer::{
-    graphics::{camera::Camera, color::Color},
-    math::{point::Point, transformations::Transformation, vector::Vector},
+    graphics::{
+        antialiasing::{Adaptive, Filter, SamplePattern},
+        camera::{Aperture, Camera, Crop, Projection},
+        canvas::Canvas,
+        color::Color,
+        settings::RenderSettings,
+    },
+    math::{
+        point::Point,
+        transformations::{Keyframe, Transformation},
+        vector::Vector,
+    },
     objects::world::World,
     testing::Testing,
 };
@@ -47,3 +57,357 @@ fn rendering_a_world() {
 
     Testing::assert_nearly_eq(image.get(5, 5), Color::new(0.38066, 0.47583, 0.2855))
 }
+
+#[test]
+fn new_camera_takes_one_sample_per_pixel() {
+    let c = Camera::new(160, 120, FRAC_PI_2);
+
+    assert_eq!(RenderSettings::default().samples, 1);
+    assert_eq!(c.sample_pattern, SamplePattern::Grid);
+    assert_eq!(c.filter, Filter::Box);
+}
+
+#[test]
+fn supersampling_smooths_edges() {
+    let mut w = World::default();
+    let mut c = Camera::new(11, 11, FRAC_PI_2);
+    c.transform = Transformation::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+
+    // pixel on the silhouette of the outer sphere
+    let aliased = c.render(w.clone()).get(4, 5);
+    w.settings.samples = 16;
+    c.sample_pattern = SamplePattern::Jittered;
+    let smoothed = c.render(w.clone()).get(4, 5);
+    c.filter = Filter::Mitchell;
+    let filtered = c.render(w).get(4, 5);
+
+    assert_ne!(aliased, smoothed);
+    assert_ne!(smoothed, filtered);
+}
+
+#[test]
+fn adaptive_sampling_refines_only_edges() {
+    // flat shaded sphere, so only the silhouette has contrast
+    let mut w = World::default();
+    w.objects[0].material.ambient = 1.0;
+    w.objects[0].material.diffuse = 0.0;
+    w.objects[0].material.specular = 0.0;
+    let mut c = Camera::new(31, 31, FRAC_PI_2);
+    c.transform = Transformation::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+    c.adaptive = Some(Adaptive::default());
+
+    let (image, counts) = c.render_with_sample_counts(w);
+
+    // empty background, and the middle of the sphere
+    assert_eq!(counts.counts[0], 5);
+    assert_eq!(counts.counts[15 * 31 + 15], 5);
+    // silhouette of the sphere
+    assert!(counts.counts[15 * 31..16 * 31]
+        .iter()
+        .any(|&count| count > 5));
+    assert_eq!(image.get(15, 15), Color::new(0.8, 1.0, 0.6));
+}
+
+#[test]
+fn sample_counts_without_adaptive_sampling() {
+    let c = Camera::new(4, 3, FRAC_PI_2);
+    let mut w = World::new();
+    w.settings.samples = 3;
+
+    let (_, counts) = c.render_with_sample_counts(w);
+
+    assert_eq!(counts.counts, vec![4; 12]);
+}
+
+#[test]
+fn new_camera_is_a_pinhole() {
+    let c = Camera::new(160, 120, FRAC_PI_2);
+
+    assert_eq!(c.aperture, 0.0);
+    assert_eq!(c.aperture_shape, Aperture::Circle);
+    assert_eq!(c.focal_distance, 1.0);
+}
+
+#[test]
+fn focusing_on_the_object_under_a_pixel() {
+    let w = World::default();
+    let mut c = Camera::new(11, 11, FRAC_PI_2);
+    c.transform = Transformation::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+
+    let focus = c.focus_on(&w, 5, 5);
+
+    Testing::assert_nearly_eq(focus.unwrap(), 4.0);
+    Testing::assert_nearly_eq(c.focal_distance, 4.0);
+    // nothing to focus on in the corner
+    assert_eq!(c.focus_on(&w, 0, 0), None);
+    Testing::assert_nearly_eq(c.focal_distance, 4.0);
+}
+
+#[test]
+fn out_of_focus_objects_are_blurred() {
+    let mut w = World::default();
+    let mut c = Camera::new(31, 31, FRAC_PI_2);
+    c.transform = Transformation::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+    w.settings.samples = 16;
+    let sharp = c.render(w.clone());
+
+    c.aperture = 0.5;
+    c.aperture_shape = Aperture::Polygon {
+        blades: 6,
+        rotation: 0.0,
+    };
+    c.focal_distance = 20.0;
+    let blurred = c.render(w.clone());
+
+    c.focus_on(&w, 15, 15);
+    let focused = c.render(w);
+
+    // the silhouette bleeds into the background when out of focus
+    let differs = |image: &raytracer::graphics::canvas::Canvas| {
+        (0..31).any(|x| image.clone().get(x, 15) != sharp.clone().get(x, 15))
+    };
+    assert!(differs(&blurred));
+    // the center of the sphere is on the focal plane, only the shading
+    // changes slightly as the eye moves across the lens
+    let (a, b) = (focused.get(15, 15), sharp.get(15, 15));
+    assert!((a.red - b.red).abs() < 0.001);
+    assert!((a.green - b.green).abs() < 0.001);
+    assert!((a.blue - b.blue).abs() < 0.001);
+}
+
+#[test]
+fn moving_objects_are_blurred_while_the_shutter_is_open() {
+    let keyframes = [
+        Keyframe::new(0.0, Transformation::translation(-1.0, 0.0, 0.0)),
+        Keyframe::new(1.0, Transformation::translation(1.0, 0.0, 0.0)),
+    ];
+    let mut w = World::default();
+    w.objects.truncate(1);
+    w.objects[0].motion = Some(&keyframes);
+
+    let mut c = Camera::new(31, 31, FRAC_PI_2);
+    c.transform = Transformation::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+    w.settings.samples = 16;
+    let start = c.render(w.clone());
+
+    c.shutter_close = 1.0;
+    let blurred = c.render(w);
+
+    // the sphere starts on the left, while the shutter is open it
+    // also covers the right side of the image
+    assert_eq!(start.clone().get(20, 15), Color::new(0.0, 0.0, 0.0));
+    assert_ne!(blurred.clone().get(20, 15), Color::new(0.0, 0.0, 0.0));
+    assert_ne!(blurred.get(10, 15), start.get(10, 15));
+}
+
+#[test]
+fn new_camera_has_perspective_projection() {
+    let c = Camera::new(160, 120, FRAC_PI_2);
+    assert_eq!(c.projection, Projection::Perspective);
+}
+
+#[test]
+fn fisheye_corners_are_black() {
+    let mut w = World::default();
+    // surround the camera, so every ray hits something
+    w.objects[0].transformation = Transformation::scaling(10.0, 10.0, 10.0);
+    w.objects.truncate(1);
+
+    let mut c = Camera::new(11, 11, std::f64::consts::PI);
+    c.projection = Projection::Fisheye;
+    let image = c.render(w);
+
+    assert_eq!(image.clone().get(0, 0), Color::new(0.0, 0.0, 0.0));
+    assert_ne!(image.get(5, 5), Color::new(0.0, 0.0, 0.0));
+}
+
+fn default_view(hsize: usize, vsize: usize) -> Camera {
+    let mut c = Camera::new(hsize, vsize, FRAC_PI_2);
+    c.transform = Transformation::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+    c
+}
+
+#[test]
+fn crop_renders_the_same_pixels_as_a_full_render() {
+    let w = World::default();
+    let c = default_view(11, 11);
+    let full = c.render(w.clone());
+
+    let crop = c.render_crop(
+        w,
+        Crop::Pixels {
+            x: 4,
+            y: 3,
+            width: 3,
+            height: 5,
+        },
+    );
+
+    assert_eq!((crop.width, crop.height), (3, 5));
+    for y in 0..5 {
+        for x in 0..3 {
+            assert_eq!(crop.clone().get(x, y), full.clone().get(x + 4, y + 3));
+        }
+    }
+}
+
+#[test]
+fn normalized_crop_covers_partial_pixels() {
+    let crop = Crop::Normalized {
+        left: 0.25,
+        top: 0.0,
+        right: 0.5,
+        bottom: 0.55,
+    };
+    assert_eq!(crop.pixels(10, 10), (2, 0, 3, 6));
+    // clamped to the image
+    let crop = Crop::Pixels {
+        x: 8,
+        y: 8,
+        width: 5,
+        height: 5,
+    };
+    assert_eq!(crop.pixels(10, 10), (8, 8, 2, 2));
+}
+
+#[test]
+fn adaptive_crop_matches_adaptive_render() {
+    let w = World::default();
+    let mut c = default_view(11, 11);
+    c.adaptive = Some(Adaptive::default());
+    let full = c.render(w.clone());
+
+    let crop = c.render_crop(
+        w,
+        Crop::Pixels {
+            x: 5,
+            y: 5,
+            width: 1,
+            height: 1,
+        },
+    );
+
+    Testing::assert_nearly_eq(crop.get(0, 0), full.get(5, 5));
+}
+
+#[test]
+fn adaptive_tiles_have_their_own_noise() {
+    let w = World::default();
+    let mut c = default_view(12, 12);
+    // lens samples are random, unrefined so only the seeding matters
+    c.aperture = 0.5;
+    c.adaptive = Some(Adaptive {
+        threshold: 0.1,
+        max_depth: 0,
+    });
+    let full = c.render(w.clone());
+
+    for (x, y) in [(0, 0), (6, 0), (0, 6), (6, 6)] {
+        let tile = c.render_crop(
+            w.clone(),
+            Crop::Pixels {
+                x,
+                y,
+                width: 6,
+                height: 6,
+            },
+        );
+        (0..36).for_each(|i| {
+            assert_eq!(
+                tile.grid[i],
+                full.grid[(y + i / 6) * 12 + x + i % 6],
+                "pixel {} of tile ({}, {})",
+                i,
+                x,
+                y
+            )
+        });
+    }
+}
+
+#[test]
+fn rendering_a_crop_into_an_existing_canvas() {
+    let w = World::default();
+    let c = default_view(11, 11);
+    let full = c.render(w.clone());
+    let red = Color::new(1.0, 0.0, 0.0);
+    let mut canvas = Canvas::new_color(11, 11, red);
+
+    c.render_crop_into(
+        w,
+        Crop::Pixels {
+            x: 5,
+            y: 5,
+            width: 2,
+            height: 1,
+        },
+        &mut canvas,
+    );
+
+    assert_eq!(canvas.clone().get(5, 5), full.clone().get(5, 5));
+    assert_eq!(canvas.clone().get(6, 5), full.get(6, 5));
+    assert_eq!(canvas.clone().get(4, 5), red);
+    assert_eq!(canvas.get(5, 6), red);
+}
+
+#[test]
+fn seed_changes_the_noise() {
+    let mut w = World::default();
+    w.objects[0].material.reflective = 0.5;
+    let mut c = default_view(11, 11);
+    c.sample_pattern = SamplePattern::Jittered;
+    w.settings.samples = 4;
+
+    let first = c.render(w.clone());
+    let again = c.render(w.clone());
+    w.settings.seed = 1;
+    let reseeded = c.render(w);
+
+    assert_eq!(first.grid, again.grid);
+    assert_ne!(first.grid, reseeded.grid);
+}
+
+#[test]
+fn mitchell_filter_keeps_flat_colors() {
+    let flat = Color::new(0.3, 0.6, 0.9);
+    let mut w = World::new();
+    w.settings.background = flat;
+    let mut c = Camera::new(30, 40, FRAC_PI_2);
+    c.filter = Filter::Mitchell;
+
+    for pattern in [SamplePattern::Jittered, SamplePattern::Halton] {
+        for samples in [1, 4] {
+            c.sample_pattern = pattern;
+            w.settings.samples = samples;
+            // negative lobes used to cancel out, leaving black pixels
+            c.render(w.clone())
+                .grid
+                .iter()
+                .for_each(|&pixel| Testing::assert_nearly_eq(pixel, flat));
+        }
+    }
+}
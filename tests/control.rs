@@ -0,0 +1,85 @@
+use std::{
+    f64::consts::FRAC_PI_2,
+    time::{Duration, Instant},
+};
+
+use raytracer::{
+    graphics::{
+        camera::{Camera, TILE_SIZE},
+        control::RenderControl,
+    },
+    math::{point::Point, transformations::Transformation, vector::Vector},
+    objects::world::World,
+};
+
+fn camera(hsize: usize, vsize: usize) -> Camera {
+    let mut c = Camera::new(hsize, vsize, FRAC_PI_2);
+    c.transform = Transformation::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+    c
+}
+
+#[test]
+fn uncancelled_render_completes_every_tile() {
+    let w = World::default();
+    let c = camera(40, 20);
+    let full = c.render(w.clone());
+
+    let partial = c.render_with_control(w, &RenderControl::new());
+
+    assert_eq!((partial.tiles_x, partial.tiles_y), (3, 2));
+    assert!(partial.is_complete());
+    assert_eq!(partial.completed_pixels(), 40 * 20);
+    assert_eq!(partial.canvas.grid, full.grid);
+}
+
+#[test]
+fn cancelled_render_stops_before_the_first_tile() {
+    let control = RenderControl::new();
+    let handle = control.clone();
+    handle.cancel();
+
+    let partial = camera(40, 20).render_with_control(World::default(), &control);
+
+    assert!(control.is_cancelled());
+    assert!(!partial.is_complete());
+    assert_eq!(partial.completed_pixels(), 0);
+    assert!(!partial.is_pixel_completed(0, 0));
+}
+
+#[test]
+fn exhausted_budget_returns_a_partial_render() {
+    let control = RenderControl::with_budget(Duration::from_secs(0));
+
+    let partial = camera(40, 20).render_with_control(World::default(), &control);
+
+    assert!(control.should_stop(Instant::now()));
+    assert!(!partial.is_complete());
+    assert_eq!(partial.canvas.width, 40);
+}
+
+#[test]
+fn budget_starts_with_the_render() {
+    let control = RenderControl::with_budget(Duration::from_millis(50));
+    // longer than the budget, before the render even began
+    std::thread::sleep(Duration::from_millis(60));
+
+    let partial = camera(40, 20).render_with_control(World::default(), &control);
+
+    assert!(partial.completed[0]);
+    assert!(partial.completed_pixels() > 0);
+}
+
+#[test]
+fn pixels_map_to_their_tile() {
+    let w = World::default();
+    let mut partial = camera(40, 20).render_with_control(w, &RenderControl::new());
+    partial.completed[1] = false;
+
+    assert!(partial.is_pixel_completed(TILE_SIZE - 1, 0));
+    assert!(!partial.is_pixel_completed(TILE_SIZE, 0));
+    assert!(partial.is_pixel_completed(TILE_SIZE, TILE_SIZE));
+}
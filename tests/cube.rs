@@ -110,3 +110,22 @@ fn normal_on_surface_of_a_cube() {
         assert_eq!(&normal, n);
     }
 }
+
+#[test]
+fn uv_mapping_on_a_cube() {
+    let c = shape::cube::default();
+
+    // center of every face
+    for point in [
+        Point::new(1.0, 0.0, 0.0),
+        Point::new(-1.0, 0.0, 0.0),
+        Point::new(0.0, 1.0, 0.0),
+        Point::new(0.0, -1.0, 0.0),
+        Point::new(0.0, 0.0, 1.0),
+        Point::new(0.0, 0.0, -1.0),
+    ] {
+        assert_eq!(c.uv_at(point), Some((0.5, 0.5)));
+    }
+    // front face, bottom left corner
+    assert_eq!(c.uv_at(Point::new(-1.0, -1.0, -1.0)), Some((0.0, 0.0)));
+}
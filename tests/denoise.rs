@@ -0,0 +1,186 @@
+use std::f64::consts::FRAC_PI_2;
+
+use raytracer::{
+    graphics::{
+        aov::{Aov, RenderPasses},
+        camera::Camera,
+        canvas::Canvas,
+        color::Color,
+        denoise::{Denoiser, Guides},
+    },
+    math::{point::Point, sampling::Pcg, transformations::Transformation, vector::Vector},
+    objects::world::World,
+    testing::Testing,
+};
+
+/// left half black, right half white, with noise
+fn noisy_edge(noise: f64) -> Canvas {
+    let mut rng = Pcg::new(0, 0);
+    let mut canvas = Canvas::new(20, 20);
+    (0..400).for_each(|i| {
+        let v = if i % 20 < 10 { 0.2 } else { 0.8 };
+        let v = v + (rng.next_f64() - 0.5) * noise;
+        canvas.write_i(i, Color::new(v, v, v));
+    });
+    canvas
+}
+
+fn clean_edge() -> Canvas {
+    noisy_edge(0.0)
+}
+
+/// mean squared error against `reference`
+fn error(image: &Canvas, reference: &Canvas) -> f64 {
+    image
+        .grid
+        .iter()
+        .zip(&reference.grid)
+        .map(|(a, b)| (a.red - b.red).powi(2))
+        .sum::<f64>()
+        / image.grid.len() as f64
+}
+
+#[test]
+fn flat_image_is_unchanged() {
+    let image = Canvas::new_color(8, 8, Color::new(0.3, 0.6, 0.9));
+
+    let denoised = Denoiser::default().denoise(&image, &Guides::default());
+
+    denoised
+        .grid
+        .iter()
+        .for_each(|&c| Testing::assert_nearly_eq(c, Color::new(0.3, 0.6, 0.9)));
+}
+
+#[test]
+fn denoising_reduces_noise() {
+    let noisy = noisy_edge(0.2);
+    let reference = clean_edge();
+
+    let denoised = Denoiser::default().denoise(&noisy, &Guides::default());
+
+    assert!(error(&denoised, &reference) < error(&noisy, &reference) / 2.0);
+}
+
+#[test]
+fn guides_keep_edges_sharp() {
+    let noisy = noisy_edge(0.2);
+    let reference = clean_edge();
+    let albedo = clean_edge();
+    // strong enough to blur across the edge without a guide
+    let denoiser = Denoiser {
+        sigma_color: 10.0,
+        ..Denoiser::default()
+    };
+
+    let unguided = denoiser.denoise(&noisy, &Guides::default());
+    let guided = denoiser.denoise(
+        &noisy,
+        &Guides {
+            albedo: Some(&albedo),
+            ..Guides::default()
+        },
+    );
+
+    // next to the edge
+    let at_edge = |image: &Canvas| image.grid[10 * 20 + 9].red;
+    assert!((at_edge(&unguided) - 0.2).abs() > 0.05);
+    assert!((at_edge(&guided) - 0.2).abs() < 0.05);
+    assert!(error(&guided, &reference) < error(&unguided, &reference));
+}
+
+#[test]
+fn depth_guide_keeps_silhouettes() {
+    let noisy = noisy_edge(0.0);
+    let mut depth = Canvas::new(20, 20);
+    (0..400).for_each(|i| {
+        let d = if i % 20 < 10 { 4.0 } else { 8.0 };
+        depth.write_i(i, Color::new(d, d, d));
+    });
+    let denoiser = Denoiser {
+        sigma_color: 10.0,
+        ..Denoiser::default()
+    };
+
+    let guided = denoiser.denoise(
+        &noisy,
+        &Guides {
+            depth: Some(&depth),
+            ..Guides::default()
+        },
+    );
+
+    Testing::assert_nearly_eq(guided.grid[10 * 20 + 9].red, 0.2);
+}
+
+#[test]
+fn passes_without_beauty_cannot_be_denoised() {
+    let passes = RenderPasses { passes: vec![] };
+
+    assert!(Denoiser::default().denoise_passes(&passes).is_none());
+}
+
+#[test]
+fn denoising_a_render() {
+    let mut c = Camera::new(11, 11, FRAC_PI_2);
+    c.transform = Transformation::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+    let passes = c.render_aovs(
+        World::default(),
+        &[Aov::Beauty, Aov::Albedo, Aov::Normal, Aov::Depth],
+    );
+
+    let denoised = Denoiser::default().denoise_passes(&passes).unwrap();
+
+    assert_eq!((denoised.width, denoised.height), (11, 11));
+    // background stays black, the sphere doesn't bleed into it
+    assert_eq!(denoised.grid[0], Color::new(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn guides_of_another_size_are_ignored() {
+    let noisy = noisy_edge(0.2);
+    let small = Canvas::new(4, 4);
+    let guides = Guides {
+        albedo: Some(&small),
+        normal: Some(&small),
+        depth: Some(&small),
+    };
+
+    let denoised = Denoiser::default().denoise(&noisy, &guides);
+
+    assert_eq!(
+        denoised.grid,
+        Denoiser::default().denoise(&noisy, &Guides::default()).grid
+    );
+}
+
+#[test]
+fn zero_sigmas_keep_the_image() {
+    let noisy = noisy_edge(0.2);
+    let albedo = clean_edge();
+    let denoiser = Denoiser {
+        sigma_spatial: 0.0,
+        sigma_color: 0.0,
+        sigma_albedo: 0.0,
+        ..Denoiser::default()
+    };
+
+    let denoised = denoiser.denoise(
+        &noisy,
+        &Guides {
+            albedo: Some(&albedo),
+            ..Guides::default()
+        },
+    );
+
+    // nothing is close enough to be averaged in
+    denoised
+        .grid
+        .iter()
+        .zip(&noisy.grid)
+        .for_each(|(&a, &b)| Testing::assert_nearly_eq(a, b));
+}
@@ -0,0 +1,225 @@
+use std::f64::consts::FRAC_PI_2;
+
+use raytracer::{
+    graphics::{
+        camera::Camera,
+        color::Color,
+        integrators::{DebugView, Integrator, Integrators, PathTracer, Whitted},
+        lights::Light,
+        materials::Material,
+    },
+    math::{
+        point::Point, ray::Ray, sampling::Pcg, transformations::Transformation, vector::Vector,
+    },
+    objects::{shape, world::World},
+    testing::Testing,
+};
+
+fn ray_at_origin() -> Ray {
+    Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0))
+}
+
+#[test]
+fn whitted_matches_color_at() {
+    let w = World::default();
+    let mut rng = Pcg::new(0, 0);
+
+    let color = Whitted.li(&w, ray_at_origin(), &mut rng);
+
+    assert_eq!(color, w.color_at(ray_at_origin(), 6));
+}
+
+#[test]
+fn renders_use_whitted_by_default() {
+    let w = World::new();
+    assert_eq!(w.settings.integrator, Integrators::Whitted(Whitted));
+}
+
+#[test]
+fn debug_views_of_a_miss_are_black() {
+    let w = World::default();
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 1.0, 0.0));
+    let mut rng = Pcg::new(0, 0);
+
+    for view in [
+        DebugView::Normals,
+        DebugView::Depth { max: 10.0 },
+        DebugView::ObjectId,
+        DebugView::Uv,
+        DebugView::FacingRatio,
+    ] {
+        assert_eq!(view.li(&w, ray, &mut rng), Color::new(0.0, 0.0, 0.0));
+    }
+}
+
+#[test]
+fn normals_view() {
+    let w = World::default();
+    let mut rng = Pcg::new(0, 0);
+
+    let color = DebugView::Normals.li(&w, ray_at_origin(), &mut rng);
+
+    // normal (0, 0, -1)
+    Testing::assert_nearly_eq(color, Color::new(0.5, 0.5, 0.0));
+}
+
+#[test]
+fn depth_view() {
+    let w = World::default();
+    let mut rng = Pcg::new(0, 0);
+
+    let color = DebugView::Depth { max: 8.0 }.li(&w, ray_at_origin(), &mut rng);
+
+    // hits at 4.0
+    Testing::assert_nearly_eq(color, Color::new(0.5, 0.5, 0.5));
+}
+
+#[test]
+fn facing_ratio_view() {
+    let w = World::default();
+    let mut rng = Pcg::new(0, 0);
+
+    let color = DebugView::FacingRatio.li(&w, ray_at_origin(), &mut rng);
+
+    Testing::assert_nearly_eq(color, Color::new(1.0, 1.0, 1.0));
+}
+
+#[test]
+fn object_id_view_differs_between_objects() {
+    let mut w = World::default();
+    w.objects[1].uid += 1;
+    w.objects[1].transformation = Transformation::translation(0.0, 3.0, 0.0);
+    let mut rng = Pcg::new(0, 0);
+    let view = DebugView::ObjectId;
+
+    let first = view.li(&w, ray_at_origin(), &mut rng);
+    let again = view.li(&w, ray_at_origin(), &mut rng);
+    let ray = Ray::new(Point::new(0.0, 3.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    let second = view.li(&w, ray, &mut rng);
+
+    assert_eq!(first, again);
+    assert_ne!(first, second);
+}
+
+#[test]
+fn rendering_with_a_debug_integrator() {
+    let mut w = World::default();
+    let mut c = Camera::new(11, 11, FRAC_PI_2);
+    c.transform = Transformation::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+    w.settings.integrator = Integrators::Debug(DebugView::Uv);
+
+    let image = c.render(w);
+
+    // front of the sphere
+    Testing::assert_nearly_eq(image.get(5, 5), Color::new(0.0, 0.5, 0.0));
+}
+
+#[test]
+fn path_tracer_without_light_is_black() {
+    let w = World {
+        lights: vec![],
+        ..World::default()
+    };
+    let mut rng = Pcg::new(0, 0);
+
+    let color = PathTracer::default().li(&w, ray_at_origin(), &mut rng);
+
+    assert_eq!(color, Color::new(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn path_tracer_replaces_ambient_with_indirect_light() {
+    // nothing for the bounce to hit, only direct light is left
+    let w = World::default();
+    let mut rng = Pcg::new(0, 0);
+
+    let color = PathTracer::default().li(&w, ray_at_origin(), &mut rng);
+
+    Testing::assert_nearly_eq(color, Color::new(0.30066, 0.37583, 0.2255));
+}
+
+#[test]
+fn path_tracer_bleeds_color_from_nearby_surfaces() {
+    let floor = shape::plane::default();
+    let red = Material {
+        color: Color::new(1.0, 0.0, 0.0),
+        ..Material::default()
+    };
+    let wall = shape::plane::new(
+        Transformation::translation(2.0, 0.0, 0.0) * Transformation::rotate_z(FRAC_PI_2),
+        red,
+    );
+    let mut w = World::new();
+    w.objects = vec![floor, wall];
+    w.lights = vec![Light::new(
+        Point::new(0.0, 5.0, 0.0),
+        Color::new(1.0, 1.0, 1.0),
+    )];
+    let ray = Ray::new(Point::new(1.5, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+    let mut rng = Pcg::new(0, 0);
+    let path_tracer = PathTracer::default();
+
+    let samples = 500;
+    let sum = (0..samples).fold(Color::new(0.0, 0.0, 0.0), |sum, _| {
+        sum + path_tracer.li(&w, ray, &mut rng)
+    });
+    let average = sum * (1.0 / samples as f64);
+
+    // direct light on the white floor is grey, the red wall adds red
+    let whitted = Whitted.li(&w, ray, &mut rng);
+    assert_eq!(whitted.red, whitted.green);
+    assert!(average.red > average.green + 0.05);
+    Testing::assert_nearly_eq(average.green, average.blue);
+}
+
+#[test]
+fn camera_renders_with_the_path_tracer() {
+    let mut w = World::default();
+    let mut c = Camera::new(51, 51, FRAC_PI_2);
+    c.transform = Transformation::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+    w.settings.integrator = Integrators::PathTracer(PathTracer::default());
+    w.settings.samples = 4;
+
+    let (image, counts) = c.render_with_sample_counts(w);
+
+    assert_eq!(counts.counts[25 * 51 + 25], 4);
+    // samples spread around the center of the sphere
+    let center = image.get(25, 25);
+    assert!((center.green - 0.37583).abs() < 0.01);
+}
+
+/// hit or miss, outside of the built-in `Integrators`
+struct Silhouette;
+
+impl Integrator for Silhouette {
+    fn li(&self, world: &World, ray: Ray, _rng: &mut Pcg) -> Color {
+        match world.intersect(ray).hit() {
+            Some(_) => Color::new(1.0, 1.0, 1.0),
+            None => Color::new(0.0, 0.0, 0.0),
+        }
+    }
+}
+
+#[test]
+fn rendering_with_a_custom_integrator() {
+    let w = World::default();
+    let mut c = Camera::new(11, 11, FRAC_PI_2);
+    c.transform = Transformation::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+
+    let image = c.render_with_integrator(w, &Silhouette);
+
+    assert_eq!(image.clone().get(5, 5), Color::new(1.0, 1.0, 1.0));
+    assert_eq!(image.get(0, 0), Color::new(0.0, 0.0, 0.0));
+}
@@ -1,5 +1,6 @@
 use raytracer::{
     constants,
+    graphics::color::Color,
     math::{point::Point, ray::Ray, transformations::Transformation, vector::Vector},
     objects::{
         intersections::{Intersection, Intersections},
@@ -262,3 +263,43 @@ fn schilck_approx_with_with_small_angle_entered_greater_than_exited() {
 
     Testing::assert_nearly_eq(reflectance, 0.48873)
 }
+
+#[test]
+fn lobe_weights_split_by_fresnel() {
+    let mut shape = sphere::glass();
+    let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0));
+    let xs = Intersection::new(-1.0, shape).agregate(Intersection::new(1.0, shape));
+    let hit = xs.get(1).unwrap();
+    let comps = hit.prepare_computations(ray, Some(&xs)).unwrap();
+    // only transparent, nothing to split
+    assert_eq!(comps.fresnel(), (1.0, 1.0));
+    assert_eq!(comps.lobe_weights(), (0.0, 1.0));
+
+    shape.material.reflective = 0.5;
+    let xs = Intersection::new(-1.0, shape).agregate(Intersection::new(1.0, shape));
+    let hit = xs.get(1).unwrap();
+    let comps = hit.prepare_computations(ray, Some(&xs)).unwrap();
+    let (reflective, transparent) = comps.lobe_weights();
+    Testing::assert_nearly_eq(reflective, 0.5 * 0.04);
+    Testing::assert_nearly_eq(transparent, 0.96);
+}
+
+#[test]
+fn material_travelled_through() {
+    let mut glass = shape::sphere::glass();
+    glass.material.absorption = Color::new(0.5, 1.0, 1.0);
+    let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    let xs = glass.intersects(&r).unwrap();
+
+    // entering from outside
+    let comps = xs.list[0].prepare_computations(r, Some(&xs)).unwrap();
+    assert_eq!(comps.exited_material, None);
+    assert_eq!(comps.transmittance(), Color::new(1.0, 1.0, 1.0));
+
+    // leaving, after 2 units of glass
+    let inside = Ray::new(Point::new(0.0, 0.0, -1.0), Vector::new(0.0, 0.0, 1.0));
+    let xs = glass.intersects(&inside).unwrap();
+    let comps = xs.list[1].prepare_computations(inside, Some(&xs)).unwrap();
+    assert_eq!(comps.exited_material, Some(glass.material));
+    assert_eq!(comps.transmittance(), Color::new(0.25, 1.0, 1.0));
+}
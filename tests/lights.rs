@@ -1,6 +1,12 @@
+use std::f64::consts::FRAC_PI_4;
+
 use raytracer::{
-    graphics::{color::Color, lights::PointLight},
-    math::point::Point,
+    constants::EPSILON,
+    graphics::{
+        color::Color,
+        lights::{Falloff, Light, LightType, PointLight, MAX_FALLOFF},
+    },
+    math::{point::Point, sampling::Pcg, vector::Vector},
 };
 
 #[test]
@@ -8,7 +14,186 @@ fn new_point_light() {
     let point = Point::new(0.0, 0.0, 0.0);
     let color = Color::new(1.0, 1.0, 1.0);
 
-    let light = PointLight::new(point, color);
+    let light = Light::new(point, color);
     assert_eq!(light.color, color);
     assert_eq!(light.position, point);
 }
+
+#[test]
+fn point_light_has_a_single_sample() {
+    let light = Light::new(Point::new(1.0, 2.0, 3.0), Color::new(1.0, 1.0, 1.0));
+    assert_eq!(light.light_type, LightType::Point);
+    assert_eq!(light.samples, 1);
+    assert_eq!(
+        light.sample_points(&mut Pcg::new(0, 0)),
+        vec![Point::new(1.0, 2.0, 3.0)]
+    );
+}
+
+#[test]
+fn rectangle_light_spans_its_edges() {
+    let light = Light::rectangle(
+        Point::new(0.0, 5.0, 0.0),
+        Vector::new(2.0, 0.0, 0.0),
+        Vector::new(0.0, 0.0, 4.0),
+        Color::new(1.0, 1.0, 1.0),
+        16,
+    );
+    assert_eq!(light.point_on(0.5, 0.5), Point::new(0.0, 5.0, 0.0));
+    assert_eq!(light.point_on(0.0, 0.0), Point::new(-1.0, 5.0, -2.0));
+    assert_eq!(light.point_on(1.0, 1.0), Point::new(1.0, 5.0, 2.0));
+
+    let points = light.sample_points(&mut Pcg::new(0, 0));
+    assert_eq!(points.len(), 16);
+    for p in points {
+        assert!(p.x.abs() <= 1.0 && p.z.abs() <= 2.0 && p.y == 5.0);
+    }
+}
+
+#[test]
+fn disk_light_points_are_on_the_disk() {
+    let light = Light::disk(
+        Point::new(0.0, 0.0, 3.0),
+        2.0,
+        Vector::new(0.0, 0.0, -5.0),
+        Color::new(1.0, 1.0, 1.0),
+        9,
+    );
+    assert_eq!(
+        light.light_type,
+        LightType::Disk {
+            radius: 2.0,
+            normal: Vector::new(0.0, 0.0, -1.0)
+        }
+    );
+    for p in light.sample_points(&mut Pcg::new(1, 0)) {
+        let offset = p - light.position;
+        assert!(offset.magnitude() <= 2.0 + EPSILON);
+        assert!(offset.z.abs() < EPSILON);
+    }
+}
+
+#[test]
+fn sphere_light_points_are_on_the_surface() {
+    let light = Light::sphere(Point::new(1.0, 1.0, 1.0), 0.5, Color::new(1.0, 1.0, 1.0), 9);
+    let points = light.sample_points(&mut Pcg::new(2, 0));
+    assert_eq!(points.len(), 9);
+    for p in points {
+        assert!(((p - light.position).magnitude() - 0.5).abs() < EPSILON);
+    }
+}
+
+#[test]
+fn spot_light_fades_between_its_cones() {
+    let white = Color::new(1.0, 1.0, 1.0);
+    let light = Light::spot(
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(0.0, 0.0, 2.0),
+        FRAC_PI_4 / 2.0,
+        FRAC_PI_4,
+        white,
+    );
+
+    // on the axis
+    assert_eq!(light.intensity_at(Point::new(0.0, 0.0, 5.0)), white);
+    // outside the outer cone, and behind
+    assert_eq!(
+        light.intensity_at(Point::new(0.0, 5.0, 1.0)),
+        Color::new(0.0, 0.0, 0.0)
+    );
+    assert_eq!(
+        light.intensity_at(Point::new(0.0, 0.0, -5.0)),
+        Color::new(0.0, 0.0, 0.0)
+    );
+    // at the apex
+    assert_eq!(light.intensity_at(Point::new(0.0, 0.0, 0.0)), white);
+    // 30 degrees off the axis, between the cones
+    let between = light.intensity_at(Point::new(0.0, 1.0, 3.0_f64.sqrt()));
+    assert!(between.red > 0.0 && between.red < 1.0);
+}
+
+#[test]
+fn directional_light_is_infinitely_far() {
+    let light = Light::directional(Vector::new(0.0, -2.0, 0.0), Color::new(1.0, 1.0, 1.0));
+    assert_eq!(
+        light.light_type,
+        LightType::Directional {
+            direction: Vector::new(0.0, -1.0, 0.0)
+        }
+    );
+    for point in [Point::new(0.0, 0.0, 0.0), Point::new(5.0, -3.0, 1.0)] {
+        assert_eq!(light.direction_from(point), Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(light.distance_from(point), f64::INFINITY);
+    }
+    assert!(!light.is_area());
+}
+
+#[test]
+fn falloff_with_distance() {
+    assert_eq!(Falloff::default(), Falloff::None);
+    assert_eq!(Falloff::None.at(4.0), 1.0);
+    assert_eq!(Falloff::Linear.at(4.0), 0.25);
+    assert_eq!(Falloff::InverseSquare.at(4.0), 0.0625);
+    let custom = Falloff::Custom {
+        constant: 1.0,
+        linear: 0.5,
+        quadratic: 0.25,
+    };
+    assert_eq!(custom.at(2.0), 1.0 / 3.0);
+}
+
+#[test]
+fn falloff_is_finite_at_the_light() {
+    assert_eq!(Falloff::Linear.at(0.0), MAX_FALLOFF);
+    assert_eq!(Falloff::InverseSquare.at(0.0), MAX_FALLOFF);
+    let zero = Falloff::Custom {
+        constant: 0.0,
+        linear: 0.0,
+        quadratic: 0.0,
+    };
+    assert_eq!(zero.at(3.0), MAX_FALLOFF);
+
+    let mut light = Light::new(Point::new(0.0, 0.0, 0.0), Color::new(1.0, 1.0, 1.0));
+    light.falloff = Falloff::InverseSquare;
+    assert!(light.intensity_at(light.position).red.is_finite());
+}
+
+#[test]
+fn power_and_falloff_scale_intensity() {
+    let mut light = Light::new(Point::new(0.0, 0.0, 0.0), Color::new(1.0, 0.5, 0.25));
+    assert_eq!(light.power, 1.0);
+    assert_eq!(light.falloff, Falloff::None);
+
+    light.power = 8.0;
+    light.falloff = Falloff::InverseSquare;
+    assert_eq!(
+        light.intensity_at(Point::new(0.0, 2.0, 0.0)),
+        Color::new(2.0, 1.0, 0.5)
+    );
+    // sunlight doesn't fall off
+    let mut sun = Light::directional(Vector::new(0.0, -1.0, 0.0), Color::new(1.0, 1.0, 1.0));
+    sun.power = 2.0;
+    sun.falloff = Falloff::InverseSquare;
+    assert_eq!(
+        sun.intensity_at(Point::new(0.0, 100.0, 0.0)),
+        Color::new(2.0, 2.0, 2.0)
+    );
+}
+
+#[test]
+fn jittered_samples_round_up_to_a_square() {
+    let light = Light::rectangle(
+        Point::new(0.0, 5.0, 0.0),
+        Vector::new(2.0, 0.0, 0.0),
+        Vector::new(0.0, 0.0, 2.0),
+        Color::new(1.0, 1.0, 1.0),
+        5,
+    );
+    assert_eq!(light.sample_points(&mut Pcg::new(0, 0)).len(), 9);
+}
+
+#[test]
+fn point_light_is_still_available() {
+    let light = PointLight::new(Point::new(0.0, 0.0, 0.0), Color::new(1.0, 1.0, 1.0));
+    assert_eq!(light.light_type, LightType::Point);
+}
@@ -5,8 +5,9 @@ use raytracer::math::point;
 use raytracer::math::transformations::Transformation;
 use raytracer::objects::shape::{self, Shape};
 use raytracer::{
-    graphics::lights::PointLight,
+    graphics::lights::{Falloff, Light},
     math::{point::Point, vector::Vector},
+    testing::Testing,
 };
 
 #[test]
@@ -30,11 +31,11 @@ fn lighting_with_eye_between_the_light_and_surface() {
 
     let eyev = Vector::new(0.0, 0.0, -1.0);
     let normal = Vector::new(0.0, 0.0, -1.0);
-    let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+    let light = Light::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
 
     let s: Shape = shape::sphere::default();
 
-    let result = material.lighting(s, light, position, eyev, normal, false);
+    let result = material.lighting(s, light, position, eyev, normal, color::WHITE);
     assert_eq!(result, Color::new(1.9, 1.9, 1.9));
 }
 
@@ -47,11 +48,11 @@ fn lighting_with_eye_between_the_light_and_surface_eye_offset_45() {
 
     let eyev = Vector::new(0.0, sqrt2_by2, -sqrt2_by2);
     let normal = Vector::new(0.0, 0.0, -1.0);
-    let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+    let light = Light::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
 
     let s: Shape = shape::sphere::default();
 
-    let result = material.lighting(s, light, position, eyev, normal, false);
+    let result = material.lighting(s, light, position, eyev, normal, color::WHITE);
     assert_eq!(result, Color::new(1.0, 1.0, 1.0));
 }
 
@@ -64,11 +65,11 @@ fn lighting_with_eye_between_the_light_and_surface_light_offset_45() {
 
     let eyev = Vector::new(0.0, 0.0, -1.0);
     let normal = Vector::new(0.0, 0.0, -1.0);
-    let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+    let light = Light::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
 
     let s: Shape = shape::sphere::default();
 
-    let result = material.lighting(s, light, position, eyev, normal, false);
+    let result = material.lighting(s, light, position, eyev, normal, color::WHITE);
 
     let expected = 0.1 + 0.9 * sqrt2_by2 + 0.0;
     assert_eq!(result, Color::new(expected, expected, expected));
@@ -83,11 +84,11 @@ fn lighting_with_eye_opposite_surface_light_offset_45() {
 
     let eyev = Vector::new(0.0, -sqrt2_by2, -sqrt2_by2);
     let normal = Vector::new(0.0, 0.0, -1.0);
-    let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+    let light = Light::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
 
     let s: Shape = shape::sphere::default();
 
-    let result = material.lighting(s, light, position, eyev, normal, false);
+    let result = material.lighting(s, light, position, eyev, normal, color::WHITE);
 
     let expected = 0.1 + 0.9 * sqrt2_by2 + 0.9;
     assert_eq!(result, Color::new(expected, expected, expected));
@@ -100,11 +101,11 @@ fn lighting_with_light_behind_surface() {
 
     let eyev = Vector::new(0.0, 0.0, -1.0);
     let normal = Vector::new(0.0, 0.0, -1.0);
-    let light = PointLight::new(Point::new(0.0, 10.0, 10.0), Color::new(1.0, 1.0, 1.0));
+    let light = Light::new(Point::new(0.0, 10.0, 10.0), Color::new(1.0, 1.0, 1.0));
 
     let s: Shape = shape::sphere::default();
 
-    let result = material.lighting(s, light, position, eyev, normal, false);
+    let result = material.lighting(s, light, position, eyev, normal, color::WHITE);
 
     let expected = 0.1;
     assert_eq!(result, Color::new(expected, expected, expected));
@@ -117,12 +118,12 @@ fn lighting_with_the_surface_in_shadow() {
 
     let eyev = Vector::new(0.0, 0.0, -1.0);
     let normal = Vector::new(0.0, 0.0, -1.0);
-    let light = PointLight::new(Point::new(0.0, 0.0, 10.0), Color::new(1.0, 1.0, 1.0));
-    let in_shadow = true;
+    let light = Light::new(Point::new(0.0, 0.0, 10.0), Color::new(1.0, 1.0, 1.0));
+    let light_attenuation = color::BLACK;
 
     let s: Shape = shape::sphere::default();
 
-    let result = material.lighting(s, light, position, eyev, normal, in_shadow);
+    let result = material.lighting(s, light, position, eyev, normal, light_attenuation);
 
     let expected = 0.1;
     assert_eq!(result, Color::new(expected, expected, expected));
@@ -147,16 +148,30 @@ fn new_material_with_pattern_applied() {
 
     let eyev = Vector::new(0.0, 0.0, -1.0);
     let normal = Vector::new(0.0, 0.0, -1.0);
-    let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
-    let in_shadow = false;
+    let light = Light::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+    let light_attenuation = color::WHITE;
 
     let s = Shape {
         material,
         ..Default::default()
     }; // useless placeholder
 
-    let c1 = material.lighting(s, light, Point::new(0.9, 0.0, 0.0), eyev, normal, in_shadow);
-    let c2 = material.lighting(s, light, Point::new(1.1, 0.0, 0.0), eyev, normal, in_shadow);
+    let c1 = material.lighting(
+        s,
+        light,
+        Point::new(0.9, 0.0, 0.0),
+        eyev,
+        normal,
+        light_attenuation,
+    );
+    let c2 = material.lighting(
+        s,
+        light,
+        Point::new(1.1, 0.0, 0.0),
+        eyev,
+        normal,
+        light_attenuation,
+    );
 
     assert_eq!(c1, color::WHITE);
     assert_eq!(c2, color::BLACK);
@@ -174,3 +189,78 @@ fn transperancy_and_refractive_index_for_default_material() {
     assert_eq!(m.refractive_index, 1.0);
     assert_eq!(m.transparency, 0.0);
 }
+
+#[test]
+fn default_material_absorbs_nothing() {
+    let m = Material::default();
+    assert_eq!(m.transmittance(10.0), Color::new(1.0, 1.0, 1.0));
+}
+
+#[test]
+fn absorption_over_distance() {
+    let m = Material {
+        absorption: Color::new(0.5, 0.8, 1.0),
+        absorption_distance: 2.0,
+        ..Material::default()
+    };
+
+    assert_eq!(m.transmittance(0.0), Color::new(1.0, 1.0, 1.0));
+    assert_eq!(m.transmittance(2.0), Color::new(0.5, 0.8, 1.0));
+    // twice as thick, twice the absorption
+    assert_eq!(m.transmittance(4.0), Color::new(0.25, 0.64, 1.0));
+}
+
+#[test]
+fn lighting_with_directional_light() {
+    let material = Material::default();
+    let eyev = Vector::new(0.0, 0.0, -1.0);
+    let normal = Vector::new(0.0, 0.0, -1.0);
+    // same as the light straight in front, but from infinitely far away
+    let light = Light::directional(Vector::new(0.0, 0.0, 3.0), Color::new(1.0, 1.0, 1.0));
+
+    let s: Shape = shape::sphere::default();
+
+    let result = material.lighting(s, light, point::ORIGIN, eyev, normal, color::WHITE);
+    assert_eq!(result, Color::new(1.9, 1.9, 1.9));
+}
+
+#[test]
+fn lighting_outside_spot_light_cone() {
+    let material = Material::default();
+    let eyev = Vector::new(0.0, 0.0, -1.0);
+    let normal = Vector::new(0.0, 0.0, -1.0);
+    // pointing up, away from the surface
+    let light = Light::spot(
+        Point::new(0.0, 0.0, -10.0),
+        Vector::new(0.0, 1.0, 0.0),
+        0.2,
+        0.4,
+        Color::new(1.0, 1.0, 1.0),
+    );
+
+    let s: Shape = shape::sphere::default();
+
+    let result = material.lighting(s, light, point::ORIGIN, eyev, normal, color::WHITE);
+    assert_eq!(result, color::BLACK);
+}
+
+#[test]
+fn lighting_with_inverse_square_falloff() {
+    let material = Material::default();
+    let eyev = Vector::new(0.0, 0.0, -1.0);
+    let normal = Vector::new(0.0, 0.0, -1.0);
+    // 10 away with 100 times the power, same as the light without falloff
+    let mut light = Light::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+    light.power = 100.0;
+    light.falloff = Falloff::InverseSquare;
+
+    let s: Shape = shape::sphere::default();
+
+    let result = material.lighting(s, light, point::ORIGIN, eyev, normal, color::WHITE);
+    Testing::assert_nearly_eq(result, Color::new(1.9, 1.9, 1.9));
+
+    // twice as far is a quarter as bright
+    light.position = Point::new(0.0, 0.0, -20.0);
+    let result = material.lighting(s, light, point::ORIGIN, eyev, normal, color::WHITE);
+    Testing::assert_nearly_eq(result, Color::new(1.9, 1.9, 1.9) * 0.25);
+}
@@ -0,0 +1,171 @@
+use std::f64::consts::PI;
+
+use raytracer::{
+    graphics::{
+        color::{self, Color},
+        lights::Light,
+        materials::Material,
+        media::{self, Medium},
+    },
+    math::{point::Point, ray::Ray, transformations::Transformation, vector::Vector},
+    objects::{shape, world::World},
+    testing::Testing,
+};
+
+/// a world with only a unit sphere of `medium` at the origin
+fn smoke_world(medium: Medium, light: Point) -> World<'static> {
+    let volume = shape::sphere::new(
+        Transformation::identity(),
+        Material {
+            medium: Some(medium),
+            ..Material::default()
+        },
+    );
+    World {
+        objects: vec![volume],
+        lights: vec![Light::new(light, Color::new(1.0, 1.0, 1.0))],
+        ..World::new()
+    }
+}
+
+#[test]
+fn isotropic_phase_function() {
+    assert!((media::henyey_greenstein(0.3, 0.0) - 1.0 / (4.0 * PI)).abs() < 1e-12);
+    assert!((Medium::default().phase(-0.7) - 1.0 / (4.0 * PI)).abs() < 1e-12);
+}
+
+#[test]
+fn phase_function_integrates_to_one() {
+    for g in [-0.8, 0.0, 0.5, 0.9] {
+        let steps = 100_000;
+        let d = 2.0 / steps as f64;
+        let sum: f64 = (0..steps)
+            .map(|i| {
+                let cos_theta = -1.0 + (i as f64 + 0.5) * d;
+                media::henyey_greenstein(cos_theta, g) * 2.0 * PI * d
+            })
+            .sum();
+        assert!((sum - 1.0).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn positive_anisotropy_scatters_forward() {
+    assert!(media::henyey_greenstein(1.0, 0.6) > media::henyey_greenstein(-1.0, 0.6));
+    assert!(media::henyey_greenstein(1.0, -0.6) < media::henyey_greenstein(-1.0, -0.6));
+}
+
+#[test]
+fn transmittance_of_a_medium() {
+    let m = Medium::new(0.2, 0.3, color::BLACK, 0.0);
+    assert_eq!(m.extinction(), 0.5);
+    assert!((m.transmittance(2.0) - (-1.0f64).exp()).abs() < 1e-12);
+    assert_eq!(m.transmittance(0.0), 1.0);
+}
+
+#[test]
+fn absorbing_atmosphere_dims_surfaces() {
+    let mut w = World::default();
+    let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    let clear = w.color_at(r, 5);
+
+    w.atmosphere = Some(Medium::new(0.1, 0.0, color::BLACK, 0.0));
+    // the sphere is hit 4 units away
+    Testing::assert_nearly_eq(w.color_at(r, 5), clear * (-0.4f64).exp());
+}
+
+#[test]
+fn atmosphere_hides_the_background() {
+    let mut w = World::default();
+    w.settings.background = Color::new(1.0, 1.0, 1.0);
+    w.atmosphere = Some(Medium::new(0.5, 0.0, color::BLACK, 0.0));
+    let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, -1.0));
+
+    let c = w.color_at(r, 5);
+    assert!(c.red < 0.01 && c.green < 0.01 && c.blue < 0.01);
+}
+
+#[test]
+fn volumes_have_no_surface() {
+    let w = smoke_world(Medium::default(), Point::new(0.0, 0.0, -10.0));
+    let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+    assert_eq!(w.intersect(r).count(), 0);
+    assert!(!w.is_shadowed(Point::new(0.0, 0.0, 5.0)));
+}
+
+#[test]
+fn spans_inside_volumes() {
+    let w = smoke_world(Medium::default(), Point::new(0.0, 0.0, -10.0));
+
+    let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    let spans = w.intersect_media(r);
+    assert_eq!(spans.len(), 1);
+    assert_eq!((spans[0].enter, spans[0].exit), (4.0, 6.0));
+
+    // starting inside
+    let r = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+    let spans = w.intersect_media(r);
+    assert_eq!((spans[0].enter, spans[0].exit), (-1.0, 1.0));
+
+    let r = Ray::new(Point::new(0.0, 5.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    assert!(w.intersect_media(r).is_empty());
+}
+
+#[test]
+fn worlds_without_media_let_everything_through() {
+    let w = World::default();
+    let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+    assert!(w.intersect_media(r).is_empty());
+    assert_eq!(
+        w.participate(r, 4.0),
+        (Color::new(1.0, 1.0, 1.0), color::BLACK)
+    );
+    assert_eq!(
+        w.media_transmittance(Point::new(0.0, 0.0, -5.0), Point::new(0.0, 0.0, 5.0), 0.0),
+        1.0
+    );
+}
+
+#[test]
+fn lit_smoke_scatters_its_color() {
+    let red = Medium::new(0.0, 0.5, Color::new(1.0, 0.0, 0.0), 0.0);
+    let w = smoke_world(red, Point::new(-10.0, 10.0, -10.0));
+    let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+    let c = w.color_at(r, 5);
+    assert!(c.red > 0.0);
+    assert_eq!((c.green, c.blue), (0.0, 0.0));
+
+    // missing the volume
+    let r = Ray::new(Point::new(0.0, 5.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    assert_eq!(w.color_at(r, 5), color::BLACK);
+}
+
+#[test]
+fn forward_scattering_glows_towards_the_light() {
+    let looking_at_light = |anisotropy: f64| {
+        let m = Medium::new(0.0, 0.5, Color::new(1.0, 1.0, 1.0), anisotropy);
+        let w = smoke_world(m, Point::new(0.0, 0.0, 10.0));
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        w.color_at(r, 5).red
+    };
+
+    assert!(looking_at_light(0.8) > looking_at_light(0.0));
+    assert!(looking_at_light(0.0) > looking_at_light(-0.8));
+}
+
+#[test]
+fn smoke_dims_what_is_behind_it() {
+    let mut w = smoke_world(
+        Medium::new(1.0, 0.0, color::BLACK, 0.0),
+        Point::new(0.0, 0.0, -10.0),
+    );
+    w.settings.background = Color::new(1.0, 1.0, 1.0);
+    let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+    // 2 units through the sphere
+    let t = (-2.0f64).exp();
+    Testing::assert_nearly_eq(w.color_at(r, 5), Color::new(t, t, t));
+}
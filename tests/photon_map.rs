@@ -0,0 +1,189 @@
+use raytracer::{
+    graphics::{
+        color::Color,
+        lights::Light,
+        photon_map::{Photon, PhotonMap},
+    },
+    math::{
+        point::Point, ray::Ray, sampling::Pcg, transformations::Transformation, vector::Vector,
+    },
+    objects::{
+        intersections::Intersections,
+        shape::{self, Shape},
+        world::World,
+    },
+};
+
+/// glass ball above a floor, light straight above
+fn caustic_world<'a>() -> World<'a> {
+    let floor = shape::plane::default();
+    let mut glass = shape::sphere::glass();
+    glass.transformation = Transformation::translation(0.0, 2.0, 0.0);
+    glass.material.diffuse = 0.0;
+
+    let mut w = World::new();
+    w.objects = vec![floor, glass];
+    w.lights = vec![Light::new(
+        Point::new(0.0, 10.0, 0.0),
+        Color::new(1.0, 1.0, 1.0),
+    )];
+    w
+}
+
+#[test]
+fn nearby_photons_match_a_brute_force_search() {
+    let mut rng = Pcg::new(1, 0);
+    let photons: Vec<Photon> = (0..500)
+        .map(|_| Photon {
+            position: Point::new(rng.next_f64(), rng.next_f64(), rng.next_f64()),
+            direction: Vector::new(0.0, -1.0, 0.0),
+            power: Color::new(1.0, 1.0, 1.0),
+        })
+        .collect();
+    let map = PhotonMap::from_photons(photons.clone(), 0.1);
+    let point = Point::new(0.5, 0.5, 0.5);
+
+    let mut found: Vec<Point> = map.nearby(point, 0.2).iter().map(|p| p.position).collect();
+    let mut expected: Vec<Point> = photons
+        .iter()
+        .filter(|p| (p.position - point).magnitude() <= 0.2)
+        .map(|p| p.position)
+        .collect();
+    let by_x = |a: &Point, b: &Point| a.x.partial_cmp(&b.x).unwrap();
+    found.sort_by(by_x);
+    expected.sort_by(by_x);
+
+    assert_eq!(map.len(), 500);
+    assert!(!expected.is_empty());
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn diffuse_only_worlds_have_no_caustics() {
+    let map = PhotonMap::build(&World::default(), 1000, 0.1);
+    assert!(map.is_empty());
+
+    let map = PhotonMap::build(&World::new(), 1000, 0.1);
+    assert!(map.is_empty());
+}
+
+#[test]
+fn glass_focuses_photons_on_the_floor() {
+    let w = caustic_world();
+    let map = PhotonMap::build(&w, 20000, 0.1);
+
+    assert!(!map.is_empty());
+    // every photon landed on the floor
+    map.nearby(Point::new(0.0, 0.0, 0.0), 100.0)
+        .iter()
+        .for_each(|p| assert!(p.position.y.abs() < 0.001));
+
+    let up = Vector::new(0.0, 1.0, 0.0);
+    let focus = map.irradiance(Point::new(0.0, 0.0, 0.0), up);
+    let aside = map.irradiance(Point::new(3.0, 0.0, 0.0), up);
+    // brighter than the light itself would be without the glass
+    assert!(focus.red > 1.0);
+    assert_eq!(aside, Color::new(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn directional_lights_take_no_share_of_the_photons() {
+    let mut w = caustic_world();
+    let alone = PhotonMap::build(&w, 5000, 0.1);
+    w.lights.push(Light::directional(
+        Vector::new(0.0, -1.0, 0.0),
+        Color::new(1.0, 1.0, 1.0),
+    ));
+    let shared = PhotonMap::build(&w, 5000, 0.1);
+
+    assert!(!alone.is_empty());
+    assert_eq!(shared, alone);
+}
+
+#[test]
+#[should_panic]
+fn photon_maps_need_a_positive_radius() {
+    PhotonMap::from_photons(vec![], 0.0);
+}
+
+#[test]
+fn shade_hit_adds_caustics() {
+    let mut w = caustic_world();
+    // look at the floor under the ball from the side
+    let ray = Ray::new(
+        Point::new(0.0, 0.5, -5.0),
+        Vector::new(0.0, -0.5, 5.0).normalize(),
+    );
+    let floor: Shape = w.objects[0];
+    let xs: Intersections = floor.intersects(&ray).unwrap();
+    let comps = xs.list[0].prepare_computations(ray, Some(&xs)).unwrap();
+    let without = w.shade_hit(&comps, 5);
+
+    w.photon_map = Some(PhotonMap::build(&w, 20000, 0.1));
+    let with = w.shade_hit(&comps, 5);
+
+    assert!(with.red > without.red);
+}
+
+/// looking at the floor right under the ball from the side
+fn under_the_glass() -> Ray {
+    Ray::new(
+        Point::new(0.0, 0.5, -5.0),
+        Vector::new(0.0, -0.5, 5.0).normalize(),
+    )
+}
+
+#[test]
+fn light_through_glass_is_not_counted_twice() {
+    let mut w = caustic_world();
+    let ray = under_the_glass();
+    let floor: Shape = w.objects[0];
+    let xs: Intersections = floor.intersects(&ray).unwrap();
+    let comps = xs.list[0].prepare_computations(ray, Some(&xs)).unwrap();
+    // straight through the glass without caustics
+    let through = w.shade_hit(&comps, 5);
+
+    // the photons already carry it, and there are none
+    w.photon_map = Some(PhotonMap::from_photons(vec![], 0.1));
+    assert!(w.shade_hit(&comps, 5).red < through.red);
+}
+
+#[test]
+fn light_reaches_through_glass_without_caustics_too() {
+    let mut w = caustic_world();
+    w.photon_map = Some(PhotonMap::from_photons(vec![], 0.1));
+    let ray = under_the_glass();
+    let floor: Shape = w.objects[0];
+    let xs: Intersections = floor.intersects(&ray).unwrap();
+    let comps = xs.list[0].prepare_computations(ray, Some(&xs)).unwrap();
+    let light = w.lights[0];
+
+    // the path tracer and the media don't add caustics
+    assert_ne!(w.surface_light(&light, &comps), Color::new(0.0, 0.0, 0.0));
+    assert_ne!(
+        w.light_transmittance(&light, w.shadow_point(&comps), 0.0),
+        Color::new(0.0, 0.0, 0.0)
+    );
+}
+
+#[test]
+fn camera_hidden_mirror_still_bounces_photons() {
+    // light between the floor and a mirror ceiling the camera can't see
+    let mut mirror = shape::plane::default();
+    mirror.transformation = Transformation::translation(0.0, 2.0, 0.0);
+    mirror.material.reflective = 1.0;
+    mirror.material.diffuse = 0.0;
+    mirror.visibility.camera = false;
+
+    let mut w = World::new();
+    w.objects = vec![shape::plane::default(), mirror];
+    w.lights = vec![Light::new(
+        Point::new(0.0, 1.0, 0.0),
+        Color::new(1.0, 1.0, 1.0),
+    )];
+
+    let map = PhotonMap::build(&w, 1000, 0.1);
+
+    // about the half that went up, reflected onto the floor
+    assert!(map.len() > 300);
+}
@@ -86,3 +86,11 @@ fn intersecets_with_a_plane_from_below() {
     assert_eq!(xs.get_intersection(1), None);
     assert_eq!(xs.get_object(0).unwrap(), s)
 }
+
+#[test]
+fn uv_mapping_on_a_plane_repeats() {
+    let p = shape::plane::default();
+
+    assert_eq!(p.uv_at(Point::new(0.25, 0.0, 0.5)), Some((0.25, 0.5)));
+    assert_eq!(p.uv_at(Point::new(1.25, 0.0, -0.5)), Some((0.25, 0.5)));
+}
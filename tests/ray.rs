@@ -55,3 +55,13 @@ fn scale_a_ray() {
     assert_eq!(r2.origin, Point::new(2.0, 6.0, 12.0));
     assert_eq!(r2.direction, Vector::new(0.0, 3.0, 0.0));
 }
+
+#[test]
+fn rays_carry_time() {
+    let ray = Ray::new(Point::new(1.0, 2.0, 3.0), Vector::new(0.0, 1.0, 0.0));
+    assert_eq!(ray.time, 0.0);
+
+    let ray = Ray::new_at_time(Point::new(1.0, 2.0, 3.0), Vector::new(0.0, 1.0, 0.0), 0.5);
+    let moved = ray.transform(Transformation::translation(3.0, 4.0, 5.0));
+    assert_eq!(moved.time, 0.5);
+}
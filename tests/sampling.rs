@@ -0,0 +1,181 @@
+use raytracer::{
+    math::{
+        sampling::{self, Pcg},
+        vector::Vector,
+    },
+    testing::Testing,
+};
+
+#[test]
+fn pcg_is_deterministic_for_seed_and_stream() {
+    let mut a = Pcg::new(42, 7);
+    let mut b = Pcg::new(42, 7);
+
+    (0..100).for_each(|_| assert_eq!(a.next_u32(), b.next_u32()));
+}
+
+#[test]
+fn pcg_streams_differ() {
+    let mut a = Pcg::new(42, 1);
+    let mut b = Pcg::new(42, 2);
+
+    let a: Vec<u32> = (0..8).map(|_| a.next_u32()).collect();
+    let b: Vec<u32> = (0..8).map(|_| b.next_u32()).collect();
+    assert_ne!(a, b);
+}
+
+#[test]
+fn pcg_floats_are_in_unit_interval() {
+    let mut rng = Pcg::new(1, 0);
+    let mean = (0..10_000).map(|_| rng.next_f64()).inspect(|x| {
+        assert!((0.0..1.0).contains(x));
+    });
+    let mean = mean.sum::<f64>() / 10_000.0;
+    assert!((mean - 0.5).abs() < 0.02);
+}
+
+#[test]
+fn radical_inverse_base_2() {
+    f64::assert_nearly_eq(sampling::radical_inverse(2, 1), 0.5);
+    f64::assert_nearly_eq(sampling::radical_inverse(2, 2), 0.25);
+    f64::assert_nearly_eq(sampling::radical_inverse(2, 3), 0.75);
+    f64::assert_nearly_eq(sampling::radical_inverse(3, 1), 1.0 / 3.0);
+}
+
+#[test]
+fn concentric_disk_stays_in_unit_disk() {
+    let mut rng = Pcg::new(3, 0);
+    (0..1000).for_each(|_| {
+        let (x, y) = sampling::concentric_disk(rng.next_f64(), rng.next_f64());
+        assert!(x * x + y * y <= 1.0 + 1e-12);
+    });
+    assert_eq!(sampling::concentric_disk(0.5, 0.5), (0.0, 0.0));
+    let (x, y) = sampling::concentric_disk(1.0, 0.5);
+    f64::assert_nearly_eq(x, 1.0);
+    f64::assert_nearly_eq(y, 0.0);
+}
+
+#[test]
+fn regular_polygon_stays_inside_polygon() {
+    let mut rng = Pcg::new(3, 0);
+    // hexagon with a corner on the x axis, the inner radius is cos(30)
+    let inner = (std::f64::consts::PI / 6.0).cos();
+    (0..1000).for_each(|_| {
+        let (x, y) = sampling::regular_polygon(6, 0.0, rng.next_f64(), rng.next_f64());
+        let r = (x * x + y * y).sqrt();
+        assert!(r <= 1.0 + 1e-12);
+        // flat edge at the top
+        assert!(y <= inner + 1e-12);
+    });
+}
+
+#[test]
+fn cosine_hemisphere_stays_above_the_surface() {
+    let mut rng = Pcg::new(3, 0);
+
+    for _ in 0..100 {
+        let (x, y, z) = sampling::cosine_hemisphere(rng.next_f64(), rng.next_f64());
+        assert!(z >= 0.0);
+        Testing::assert_nearly_eq(x * x + y * y + z * z, 1.0);
+    }
+    // center of the square is the pole
+    assert_eq!(sampling::cosine_hemisphere(0.5, 0.5), (0.0, 0.0, 1.0));
+}
+
+#[test]
+fn pixel_streams_are_independent() {
+    let mut a = Pcg::for_pixel(1, 3, 4, 10);
+    let mut b = Pcg::for_pixel(1, 4, 3, 10);
+    let mut same = Pcg::new(1, 43);
+
+    let first = a.next_u32();
+    assert_ne!(first, b.next_u32());
+    assert_eq!(first, same.next_u32());
+}
+
+#[test]
+fn split_generators_differ() {
+    let mut rng = Pcg::new(5, 0);
+    let mut a = rng.split();
+    let mut b = rng.split();
+
+    assert_ne!(a.next_u32(), b.next_u32());
+}
+
+#[test]
+fn halton_uses_a_prime_per_dimension() {
+    Testing::assert_nearly_eq(sampling::halton(1, 0), 0.5);
+    Testing::assert_nearly_eq(sampling::halton(1, 1), 1.0 / 3.0);
+    Testing::assert_nearly_eq(sampling::halton(1, 2), 1.0 / 5.0);
+    Testing::assert_nearly_eq(sampling::halton(3, 0), 0.75);
+}
+
+#[test]
+fn sobol_first_points() {
+    let points: Vec<(f64, f64)> = (0..4)
+        .map(|i| (sampling::sobol(i, 0), sampling::sobol(i, 1)))
+        .collect();
+
+    assert_eq!(
+        points,
+        vec![(0.0, 0.0), (0.5, 0.5), (0.25, 0.75), (0.75, 0.25)]
+    );
+}
+
+#[test]
+fn sobol_points_are_stratified() {
+    // any power of two points has exactly one point in every 1/n interval
+    for dimension in 0..8 {
+        let mut strata = [false; 16];
+        (0..16).for_each(|i| {
+            let v = sampling::sobol(i, dimension);
+            assert!((0.0..1.0).contains(&v));
+            strata[(v * 16.0) as usize] = true;
+        });
+        assert!(strata.iter().all(|&hit| hit), "dimension {}", dimension);
+    }
+}
+
+#[test]
+fn uniform_directions_are_unit_length() {
+    let mut rng = Pcg::new(9, 0);
+
+    for _ in 0..100 {
+        let (u, v) = (rng.next_f64(), rng.next_f64());
+        let (x, y, z) = sampling::uniform_hemisphere(u, v);
+        Testing::assert_nearly_eq(x * x + y * y + z * z, 1.0);
+        assert!(z >= 0.0);
+
+        let (x, y, z) = sampling::uniform_sphere(u, v);
+        Testing::assert_nearly_eq(x * x + y * y + z * z, 1.0);
+    }
+}
+
+#[test]
+fn uniform_triangle_stays_inside() {
+    let mut rng = Pcg::new(11, 0);
+
+    for _ in 0..100 {
+        let (b0, b1) = sampling::uniform_triangle(rng.next_f64(), rng.next_f64());
+        assert!(b0 >= 0.0 && b1 >= 0.0 && b0 + b1 <= 1.0);
+    }
+}
+
+#[test]
+fn basis_around_is_orthonormal() {
+    let normals = [
+        Vector::new(0.0, 0.0, 1.0),
+        Vector::new(1.0, 0.0, 0.0),
+        Vector::new(0.0, -1.0, 0.0),
+        Vector::new(1.0, 2.0, -3.0).normalize(),
+    ];
+
+    for normal in normals.iter() {
+        let (tangent, bitangent) = sampling::basis_around(*normal);
+        Testing::assert_nearly_eq(tangent.magnitude(), 1.0);
+        Testing::assert_nearly_eq(bitangent.magnitude(), 1.0);
+        Testing::assert_nearly_eq(tangent.dot_product(normal), 0.0);
+        Testing::assert_nearly_eq(bitangent.dot_product(normal), 0.0);
+        Testing::assert_nearly_eq(tangent.dot_product(&bitangent), 0.0);
+    }
+}
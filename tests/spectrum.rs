@@ -0,0 +1,113 @@
+use std::f64::consts::FRAC_PI_2;
+
+use raytracer::{
+    graphics::{
+        camera::Camera,
+        color::Color,
+        materials::{Dispersion, Material},
+        spectrum::{self, MAX_WAVELENGTH, MIN_WAVELENGTH},
+    },
+    math::{point::Point, ray::Ray, transformations::Transformation, vector::Vector},
+    objects::{intersections::Intersections, shape, world::World},
+    testing::Testing,
+};
+
+#[test]
+fn luminance_peaks_in_the_green() {
+    let (_, y, _) = spectrum::cie_xyz(555.0);
+    assert!((y - 1.0).abs() < 0.01);
+
+    let (_, y, _) = spectrum::cie_xyz(450.0);
+    assert!(y < 0.1);
+}
+
+#[test]
+fn wavelengths_average_to_white() {
+    let steps = 1000;
+    let step = (MAX_WAVELENGTH - MIN_WAVELENGTH) / steps as f64;
+    let sum = (0..steps).fold(Color::new(0.0, 0.0, 0.0), |sum, i| {
+        sum + spectrum::wavelength_to_rgb(MIN_WAVELENGTH + (i as f64 + 0.5) * step)
+    });
+
+    Testing::assert_nearly_eq(sum * (1.0 / steps as f64), Color::new(1.0, 1.0, 1.0));
+}
+
+#[test]
+fn red_light_is_red() {
+    let red = spectrum::wavelength_to_rgb(650.0);
+    assert!(red.red > red.green && red.red > red.blue);
+
+    let blue = spectrum::wavelength_to_rgb(450.0);
+    assert!(blue.blue > blue.red && blue.blue > blue.green);
+}
+
+#[test]
+fn dispersion_formulas() {
+    // BK7 at the sodium d line
+    assert!((Dispersion::CROWN_GLASS.index(587.6) - 1.5168).abs() < 0.0005);
+    assert!((Dispersion::DIAMOND.index(589.0) - 2.417).abs() < 0.005);
+
+    let cauchy = Dispersion::Cauchy {
+        a: 1.5046,
+        b: 0.0042,
+    };
+    // blue bends more than red
+    assert!(cauchy.index(450.0) > cauchy.index(650.0));
+    assert!(Dispersion::CROWN_GLASS.index(450.0) > Dispersion::CROWN_GLASS.index(650.0));
+}
+
+#[test]
+fn refractive_index_only_disperses_with_a_wavelength() {
+    let mut m = Material::default();
+    m.refractive_index = 1.5;
+    assert_eq!(m.refractive_index_at(Some(450.0)), 1.5);
+
+    m.dispersion = Some(Dispersion::CROWN_GLASS);
+    assert_eq!(m.refractive_index_at(None), 1.5);
+    assert_ne!(m.refractive_index_at(Some(450.0)), 1.5);
+}
+
+#[test]
+fn wavelengths_refract_differently() {
+    let mut glass = shape::sphere::glass();
+    glass.material.dispersion = Some(Dispersion::DIAMOND);
+    let entered = |wavelength: f64| {
+        let ray = Ray {
+            wavelength: Some(wavelength),
+            ..Ray::new(Point::new(0.0, 0.5, -5.0), Vector::new(0.0, 0.0, 1.0))
+        };
+        let xs: Intersections = glass.intersects(&ray).unwrap();
+        let comps = xs.list[0].prepare_computations(ray, Some(&xs)).unwrap();
+        assert_eq!(comps.wavelength, Some(wavelength));
+        (
+            comps.refractive_entered,
+            comps.refracted_direction().unwrap(),
+        )
+    };
+
+    let (blue_index, blue) = entered(450.0);
+    let (red_index, red) = entered(650.0);
+
+    assert!(blue_index > red_index);
+    assert_ne!(blue, red);
+}
+
+#[test]
+fn spectral_render_without_dispersion_matches_rgb() {
+    let mut w = World::default();
+    let mut c = Camera::new(11, 11, FRAC_PI_2);
+    c.transform = Transformation::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+    let rgb = c.render(w.clone()).get(5, 5);
+
+    w.settings.spectral = true;
+    w.settings.samples = 2000;
+    let spectral = c.render(w).get(5, 5);
+
+    assert!((spectral.red - rgb.red).abs() < 0.05);
+    assert!((spectral.green - rgb.green).abs() < 0.05);
+    assert!((spectral.blue - rgb.blue).abs() < 0.05);
+}
@@ -5,7 +5,7 @@ use raytracer::{
     math::{
         point::Point,
         ray::Ray,
-        transformations::Transformation,
+        transformations::{Keyframe, Transformation},
         vector::{self, Vector},
     },
     objects::shape::{self, Shape},
@@ -198,3 +198,44 @@ fn sphere_given_material() {
     s.material = m;
     assert_eq!(s.material, m)
 }
+
+#[test]
+fn moving_sphere_is_intersected_where_it_is_at_ray_time() {
+    let keyframes = [
+        Keyframe::new(0.0, Transformation::identity()),
+        Keyframe::new(1.0, Transformation::translation(0.0, 0.0, 2.0)),
+    ];
+    let mut s: Shape = shape::sphere::default();
+    s.motion = Some(&keyframes);
+
+    let origin = Point::new(0.0, 0.0, -5.0);
+    let direction = Vector::new(0.0, 0.0, 1.0);
+
+    let xs = s
+        .intersects(&Ray::new_at_time(origin, direction, 0.0))
+        .unwrap();
+    assert_eq!(xs.get_intersection(0).unwrap(), 4.0);
+
+    let xs = s
+        .intersects(&Ray::new_at_time(origin, direction, 0.5))
+        .unwrap();
+    assert_eq!(xs.get_intersection(0).unwrap(), 5.0);
+
+    // intersections know where the sphere was, so normals are correct
+    let hit = xs.get_object(0).unwrap();
+    let normal = hit.normal_at(Point::new(0.0, 0.0, 0.0)).unwrap();
+    Testing::assert_nearly_eq(normal, Vector::new(0.0, 0.0, -1.0));
+}
+
+#[test]
+fn uv_mapping_on_a_sphere() {
+    let s = shape::sphere::default();
+
+    let (u, v) = s.uv_at(Point::new(0.0, 0.0, -1.0)).unwrap();
+    Testing::assert_nearly_eq(u, 0.0);
+    Testing::assert_nearly_eq(v, 0.5);
+    let (u, _) = s.uv_at(Point::new(1.0, 0.0, 0.0)).unwrap();
+    Testing::assert_nearly_eq(u, 0.25);
+    let (_, v) = s.uv_at(Point::new(0.0, 1.0, 0.0)).unwrap();
+    Testing::assert_nearly_eq(v, 1.0);
+}
@@ -0,0 +1,127 @@
+use std::f64::consts::FRAC_PI_2;
+
+use raytracer::{
+    graphics::{
+        camera::Camera,
+        canvas::Canvas,
+        color::{self, Color},
+        materials::Material,
+        stereo::{StereoCamera, StereoLayout, StereoMode},
+    },
+    math::{
+        point::{self, Point},
+        transformations::Transformation,
+    },
+    objects::{shape, world::World},
+    testing::Testing,
+};
+
+fn rig() -> StereoCamera {
+    let camera = Camera::new(21, 21, FRAC_PI_2);
+    StereoCamera::new(camera, 0.5, 5.0)
+}
+
+#[test]
+fn eyes_are_apart_by_interocular_distance() {
+    let rig = rig();
+    let left = rig.left().transform.inverse().unwrap() * point::ORIGIN;
+    let right = rig.right().transform.inverse().unwrap() * point::ORIGIN;
+
+    // camera x axis points to the left
+    Testing::assert_nearly_eq(left, Point::new(0.25, 0.0, 0.0));
+    Testing::assert_nearly_eq(right, Point::new(-0.25, 0.0, 0.0));
+}
+
+#[test]
+fn parallel_eyes_shift_their_canvas() {
+    let rig = rig();
+
+    assert_eq!(rig.mode, StereoMode::Parallel);
+    f64::assert_nearly_eq(rig.left().shift_x, -0.05);
+    f64::assert_nearly_eq(rig.right().shift_x, 0.05);
+}
+
+#[test]
+fn toe_in_eyes_look_at_convergence_point() {
+    let mut rig = rig();
+    rig.mode = StereoMode::ToeIn;
+    let convergence = Point::new(0.0, 0.0, -5.0);
+
+    for eye in [rig.left(), rig.right()] {
+        let p = eye.transform * convergence;
+        f64::assert_nearly_eq(p.x, 0.0);
+        assert_eq!(eye.shift_x, 0.0);
+    }
+}
+
+#[test]
+fn both_eyes_see_the_convergence_point_in_the_center() {
+    let mut world = World::new();
+    world.lights = World::default().lights;
+    world.objects = vec![shape::sphere::new(
+        Transformation::translation(0.0, 0.0, -5.0) * Transformation::scaling(0.1, 0.1, 0.1),
+        Material::default(),
+    )];
+
+    for mode in [StereoMode::Parallel, StereoMode::ToeIn] {
+        let mut rig = rig();
+        rig.mode = mode;
+
+        let left = rig.left().render(world.clone());
+        let right = rig.right().render(world.clone());
+        assert_ne!(left.get(10, 10), color::BLACK);
+        assert_ne!(right.get(10, 10), color::BLACK);
+    }
+}
+
+#[test]
+fn zero_convergence_gives_parallel_unshifted_eyes() {
+    for mode in [StereoMode::Parallel, StereoMode::ToeIn] {
+        for convergence in [0.0, -1.0, f64::INFINITY] {
+            let mut rig = rig();
+            rig.mode = mode;
+            rig.convergence = convergence;
+
+            let left = rig.left();
+            assert_eq!(left.shift_x, 0.0);
+            assert_eq!(
+                left.transform,
+                Transformation::translation(-0.25, 0.0, 0.0) * rig.camera.transform
+            );
+            // no NaN rays
+            let image = rig.render(World::default());
+            assert!(image.grid.iter().all(|c| c.red.is_finite()));
+        }
+    }
+}
+
+#[test]
+fn side_by_side_and_top_bottom_layouts() {
+    let mut rig = rig();
+    let left = Canvas::new_color(2, 1, color::RED);
+    let right = Canvas::new_color(2, 1, color::BLUE);
+
+    let canvas = rig.compose(&left, &right);
+    assert_eq!((canvas.width, canvas.height), (4, 1));
+    assert_eq!(
+        canvas.grid,
+        vec![color::RED, color::RED, color::BLUE, color::BLUE]
+    );
+
+    rig.layout = StereoLayout::TopBottom;
+    let canvas = rig.compose(&left, &right);
+    assert_eq!((canvas.width, canvas.height), (2, 2));
+    assert_eq!(canvas.clone().get(1, 0), color::RED);
+    assert_eq!(canvas.get(1, 1), color::BLUE);
+}
+
+#[test]
+fn anaglyph_takes_red_from_the_left_eye() {
+    let mut rig = rig();
+    rig.layout = StereoLayout::Anaglyph;
+    let left = Canvas::new_color(1, 1, Color::new(0.2, 0.4, 0.6));
+    let right = Canvas::new_color(1, 1, Color::new(0.8, 0.5, 0.1));
+
+    let canvas = rig.compose(&left, &right);
+    assert_eq!(canvas.get(0, 0), Color::new(0.2, 0.5, 0.1));
+}
@@ -265,3 +265,81 @@ mod orientation {
         )
     }
 }
+
+mod interpolation {
+    use super::*;
+    use raytracer::{math::transformations::Keyframe, testing::Testing};
+
+    #[test]
+    fn halfway_translation() {
+        let a = Transformation::translation(0.0, 0.0, 0.0);
+        let b = Transformation::translation(10.0, -4.0, 2.0);
+        let p = a.interpolate(&b, 0.5) * Point::new(0.0, 0.0, 0.0);
+
+        Testing::assert_nearly_eq(p, Point::new(5.0, -2.0, 1.0));
+    }
+
+    #[test]
+    fn rotation_follows_the_arc() {
+        let a = Transformation::identity();
+        let b = Transformation::rotate_y(PI / 2.0);
+        let p = a.interpolate(&b, 0.5) * Point::new(0.0, 0.0, 1.0);
+
+        // a plain blend of the matrices would give a point inside the circle
+        let expected = Transformation::rotate_y(PI / 4.0) * Point::new(0.0, 0.0, 1.0);
+        Testing::assert_nearly_eq(p, expected);
+    }
+
+    #[test]
+    fn translation_rotation_and_scale_together() {
+        let a = Transformation::translation(1.0, 0.0, 0.0) * Transformation::scaling(1.0, 1.0, 1.0);
+        let b = Transformation::translation(3.0, 0.0, 0.0)
+            * Transformation::rotate_z(PI / 2.0)
+            * Transformation::scaling(3.0, 3.0, 3.0);
+        let halfway = a.interpolate(&b, 0.5);
+
+        let expected = Transformation::translation(2.0, 0.0, 0.0)
+            * Transformation::rotate_z(PI / 4.0)
+            * Transformation::scaling(2.0, 2.0, 2.0);
+        let p = Point::new(1.0, 1.0, 1.0);
+        Testing::assert_nearly_eq(halfway * p, expected * p);
+    }
+
+    #[test]
+    fn keyframes_at_time() {
+        let keyframes = [
+            Keyframe::new(0.0, Transformation::translation(0.0, 0.0, 0.0)),
+            Keyframe::new(1.0, Transformation::translation(2.0, 0.0, 0.0)),
+            Keyframe::new(3.0, Transformation::translation(2.0, 4.0, 0.0)),
+        ];
+        let at =
+            |time| Transformation::at_time(&keyframes, time).unwrap() * Point::new(0.0, 0.0, 0.0);
+
+        Testing::assert_nearly_eq(at(-1.0), Point::new(0.0, 0.0, 0.0));
+        Testing::assert_nearly_eq(at(0.5), Point::new(1.0, 0.0, 0.0));
+        Testing::assert_nearly_eq(at(2.0), Point::new(2.0, 2.0, 0.0));
+        Testing::assert_nearly_eq(at(5.0), Point::new(2.0, 4.0, 0.0));
+        assert_eq!(Transformation::at_time(&[], 1.0), None);
+    }
+
+    #[test]
+    fn mirrored_keyframes_stay_mirrored() {
+        let mirror = Transformation::scaling(-1.0, 1.0, 1.0);
+        let keyframes = [Keyframe::new(0.0, mirror), Keyframe::new(1.0, mirror)];
+        let p = Point::new(1.0, 2.0, 3.0);
+
+        let halfway = Transformation::at_time(&keyframes, 0.5).unwrap();
+        Testing::assert_nearly_eq(halfway * p, Point::new(-1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn mirror_turning_halfway() {
+        let a = Transformation::scaling(1.0, 1.0, -2.0);
+        let b = Transformation::rotate_y(PI / 2.0) * Transformation::scaling(1.0, 1.0, -2.0);
+        let p = Point::new(0.0, 0.0, 1.0);
+
+        let expected =
+            Transformation::rotate_y(PI / 4.0) * Transformation::scaling(1.0, 1.0, -2.0) * p;
+        Testing::assert_nearly_eq(a.interpolate(&b, 0.5) * p, expected);
+    }
+}
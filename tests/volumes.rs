@@ -0,0 +1,197 @@
+use raytracer::{
+    graphics::{
+        color::{self, Color},
+        lights::Light,
+        materials::Material,
+        media::Medium,
+        volumes::{Density, NoiseField, VoxelGrid},
+    },
+    math::{
+        point::Point, ray::Ray, sampling::Pcg, transformations::Transformation, vector::Vector,
+    },
+    objects::{cube, shape::Shape, world::World},
+};
+
+/// a world with only a cube (`-1..1`) of `medium` filled with `density`
+fn cloud_world(medium: Medium, density: &Density) -> World<'_> {
+    let volume = Shape {
+        density: Some(density),
+        ..cube::new(
+            Transformation::identity(),
+            Material {
+                medium: Some(medium),
+                ..Material::default()
+            },
+        )
+    };
+    World {
+        objects: vec![volume],
+        lights: vec![Light::new(
+            Point::new(-10.0, 10.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        )],
+        ..World::new()
+    }
+}
+
+#[test]
+fn voxel_grid_needs_every_voxel() {
+    assert!(VoxelGrid::new(2, 2, 2, vec![0.0; 8]).is_some());
+    assert!(VoxelGrid::new(2, 2, 2, vec![0.0; 7]).is_none());
+    assert!(VoxelGrid::from_raw(&[0; 9], 2, 2, 2).is_none());
+}
+
+#[test]
+fn raw_voxels_are_bytes() {
+    let bytes: Vec<u8> = (0..8).map(|i| if i == 7 { 255 } else { 0 }).collect();
+    let grid = VoxelGrid::from_raw(&bytes, 2, 2, 2).unwrap();
+
+    assert_eq!(grid.get(1, 1, 1), 1.0);
+    assert_eq!(grid.get(0, 1, 1), 0.0);
+    assert_eq!(Density::Grid(grid).max(), 1.0);
+}
+
+#[test]
+fn loading_raw_voxels() {
+    let path = std::env::temp_dir().join("raytracer_voxels.raw");
+    std::fs::write(&path, [0u8, 51, 102, 153, 204, 255, 0, 0]).unwrap();
+
+    let grid = VoxelGrid::load_raw(&path, 2, 2, 2).unwrap();
+    assert_eq!(grid.get(1, 0, 0), 0.2);
+
+    let err = VoxelGrid::load_raw(&path, 3, 3, 3).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn voxel_grid_interpolates_between_centers() {
+    // two voxels along x, centers at x = -0.5 and x = 0.5
+    let grid = VoxelGrid::new(2, 1, 1, vec![0.0, 1.0]).unwrap();
+
+    assert_eq!(grid.at(Point::new(-0.5, 0.0, 0.0)), 0.0);
+    assert_eq!(grid.at(Point::new(0.5, 0.0, 0.0)), 1.0);
+    assert!((grid.at(Point::new(0.0, 0.3, 0.0)) - 0.5).abs() < 1e-12);
+    // clamped to the edge voxels, empty outside
+    assert_eq!(grid.at(Point::new(0.9, 0.0, 0.0)), 1.0);
+    assert_eq!(grid.at(Point::new(1.1, 0.0, 0.0)), 0.0);
+}
+
+#[test]
+fn noise_is_seeded() {
+    let a = NoiseField::new(1, 2.0, 4, 0.3);
+    let b = NoiseField::new(1, 2.0, 4, 0.3);
+    let c = NoiseField::new(2, 2.0, 4, 0.3);
+    let p = Point::new(0.37, -0.61, 0.12);
+
+    assert_eq!(a.at(p), b.at(p));
+    assert_ne!(a.perlin(0.37, -0.61, 0.12), c.perlin(0.37, -0.61, 0.12));
+}
+
+#[test]
+fn noise_density_is_bounded() {
+    let noise = NoiseField::new(7, 3.0, 5, 0.4);
+    let mut rng = Pcg::new(0, 0);
+    let mut empty = 0;
+    for _ in 0..2000 {
+        let p = Point::new(rng.next_f64(), rng.next_f64(), rng.next_f64());
+        let d = noise.at(p);
+        assert!((0.0..=1.0).contains(&d));
+        assert!(noise.perlin(p.x * 10.0, p.y * 10.0, p.z * 10.0).abs() <= 1.1);
+        if d == 0.0 {
+            empty += 1;
+        }
+    }
+    // the threshold clears some space between the clouds
+    assert!(empty > 0 && empty < 2000);
+}
+
+#[test]
+fn noise_threshold_stays_below_one() {
+    let noise = NoiseField::new(7, 3.0, 5, 1.0);
+    assert!(noise.threshold < 1.0);
+
+    let mut rng = Pcg::new(0, 0);
+    for _ in 0..200 {
+        let p = Point::new(rng.next_f64(), rng.next_f64(), rng.next_f64());
+        assert!((0.0..=1.0).contains(&noise.at(p)));
+    }
+}
+
+#[test]
+fn perlin_noise_is_zero_on_the_lattice() {
+    let noise = NoiseField::new(3, 1.0, 1, 0.0);
+    assert_eq!(noise.perlin(1.0, 2.0, 3.0), 0.0);
+}
+
+#[test]
+fn spans_know_their_density() {
+    let grid = Density::Grid(VoxelGrid::new(2, 1, 1, vec![0.0, 0.5]).unwrap());
+    let w = cloud_world(Medium::default(), &grid);
+    let r = Ray::new(Point::new(-5.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0));
+
+    let spans = w.intersect_media(r);
+    assert_eq!(spans.len(), 1);
+    assert_eq!(spans[0].max_density(), 0.5);
+    assert_eq!(spans[0].density_at(Point::new(0.5, 0.0, 0.0), 0.0), 0.5);
+}
+
+#[test]
+fn ratio_tracking_matches_beer_lambert() {
+    // half of the cube is empty, the other half has density 1
+    let grid = Density::Grid(VoxelGrid::new(2, 1, 1, vec![1.0, 1.0]).unwrap());
+    let medium = Medium::new(0.5, 0.0, color::BLACK, 0.0);
+    let w = cloud_world(medium, &grid);
+
+    // estimates average to exp(-0.5 * 2)
+    let n = 4000;
+    let mean: f64 = (0..n)
+        .map(|i| {
+            let from = Point::new(-5.0, 0.0, 1e-6 * i as f64);
+            w.media_transmittance(from, Point::new(5.0, 0.0, 0.0), 0.0)
+        })
+        .sum::<f64>()
+        / n as f64;
+    assert!((mean - (-1.0f64).exp()).abs() < 0.02);
+}
+
+#[test]
+fn empty_grid_lets_everything_through() {
+    let grid = Density::Grid(VoxelGrid::new(2, 2, 2, vec![0.0; 8]).unwrap());
+    let mut w = cloud_world(Medium::default(), &grid);
+    w.settings.background = Color::new(1.0, 1.0, 1.0);
+    let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+    assert_eq!(w.color_at(r, 5), Color::new(1.0, 1.0, 1.0));
+}
+
+#[test]
+fn delta_tracking_matches_marching_on_average() {
+    // a constant grid is a homogeneous medium
+    let grid = Density::Grid(VoxelGrid::new(1, 1, 1, vec![1.0]).unwrap());
+    let medium = Medium::new(0.2, 0.6, Color::new(1.0, 1.0, 1.0), 0.0);
+    let mut heterogeneous = cloud_world(medium, &grid);
+    heterogeneous.settings.background = Color::new(0.2, 0.2, 0.2);
+    let mut homogeneous = heterogeneous.clone();
+    homogeneous.objects[0].density = None;
+    homogeneous.settings.volume_steps = 64;
+
+    let expected = homogeneous
+        .color_at(
+            Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0)),
+            5,
+        )
+        .red;
+    let n = 4000;
+    let mean: f64 = (0..n)
+        .map(|i| {
+            let r = Ray::new(
+                Point::new(1e-6 * i as f64, 0.0, -5.0),
+                Vector::new(0.0, 0.0, 1.0),
+            );
+            heterogeneous.color_at(r, 5).red
+        })
+        .sum::<f64>()
+        / n as f64;
+    assert!((mean - expected).abs() < 0.02);
+}
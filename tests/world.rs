@@ -1,16 +1,19 @@
-use std::f64::consts::SQRT_2;
+use std::f64::consts::{FRAC_PI_2, SQRT_2};
 
 use raytracer::{
-    constants::MAX_REFLECTION_RECRUSTION,
+    constants::{EPSILON, MAX_REFLECTION_RECRUSTION},
     graphics::{
+        antialiasing::Adaptive,
+        aov::Aov,
+        camera::Camera,
         color::{self, Color},
-        lights::PointLight,
+        lights::Light,
         materials::Material,
         patterns::Pattern,
     },
     math::{
         point::{self, Point},
-        ray::Ray,
+        ray::{Ray, RayKind},
         transformations::Transformation,
         vector::Vector,
     },
@@ -25,7 +28,7 @@ use raytracer::{
 
 #[test]
 fn default_world() {
-    let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+    let light = Light::new(Point::new(-10.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
     let mut s1 = sphere::default();
     s1.material.color = Color::new(0.8, 1.0, 0.6);
     s1.material.diffuse = 0.7;
@@ -33,7 +36,7 @@ fn default_world() {
 
     let w = World::default();
 
-    assert_eq!(w.light.unwrap(), light);
+    assert_eq!(w.lights, vec![light]);
     println!("{:#?}", s1);
     println!("{:#?}", w.objects[0]);
     // shperes got uuids!
@@ -95,10 +98,10 @@ fn shading_an_intersection() {
 #[test]
 fn shading_an_intersection_from_inside() {
     let w = World {
-        light: Some(PointLight::new(
+        lights: vec![Light::new(
             Point::new(0.0, 0.25, 0.0),
             Color::new(1.0, 1.0, 1.0),
-        )),
+        )],
         ..Default::default()
     };
 
@@ -190,10 +193,10 @@ fn no_shadow_when_object_is_behind_point() {
 #[test]
 fn intersection_is_shadow() {
     let mut w = World::new();
-    w.light = Some(PointLight::new(
+    w.lights = vec![Light::new(
         Point::new(0.0, 0.0, -10.0),
         Color::new(1.0, 1.0, 1.0),
-    ));
+    )];
     let r = Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0));
     let s1 = shape::sphere::default();
     let s2 = shape::sphere::new(
@@ -316,7 +319,7 @@ fn shade_hit_with_reflective_material() {
 fn shade_hit_with_infinite_recursion() {
     // to plane mirrors facing each other with a ray reflecting for infinity
     let mut w = World::new();
-    w.light = Some(PointLight::new(point::ORIGIN, color::BLACK));
+    w.lights = vec![Light::new(point::ORIGIN, color::BLACK)];
 
     let mut material = Material::default();
     material.reflective = 0.5;
@@ -356,7 +359,7 @@ fn refracted_color_for_opaque_surface() {
 fn refracted_color_with_max_recursion_depth() {
     // to plane mirrors facing each other with a ray reflecting for infinity
     let mut w = World::default();
-    // w.light = Some(PointLight::new(point::ORIGIN, color::BLACK));
+    // w.lights = vec![Light::new(point::ORIGIN, color::BLACK)];
 
     let shape = &mut w.objects[0];
     shape.material.transparency = 1.0;
@@ -379,7 +382,7 @@ fn refracted_color_with_max_recursion_depth() {
 fn refracted_color_under_total_internal_reflection() {
     // to plane mirrors facing each other with a ray reflecting for infinity
     let mut w = World::default();
-    // w.light = Some(PointLight::new(point::ORIGIN, color::BLACK));
+    // w.lights = vec![Light::new(point::ORIGIN, color::BLACK)];
 
     let shape = &mut w.objects[0];
     shape.material.transparency = 1.0;
@@ -464,7 +467,8 @@ fn shade_hit_with_transperant_material() {
     let comps = i1.prepare_computations(ray, Some(&xs)).unwrap();
     let color = world.shade_hit(&comps, 5);
 
-    Testing::assert_nearly_eq(color, Color::new(0.93642, 0.68642, 0.68642));
+    // the half transparent floor lets half of the light through to the ball
+    Testing::assert_nearly_eq(color, Color::new(1.12547, 0.68643, 0.68643));
 }
 
 #[test]
@@ -499,5 +503,444 @@ fn shade_hit_with_reflective_transperant_material() {
     let comps = i1.prepare_computations(ray, Some(&xs)).unwrap();
     let color = world.shade_hit(&comps, 5);
 
-    Testing::assert_nearly_eq(color, Color::new(0.93391, 0.69643, 0.69243));
+    // the ball is half lit through the floor, see `shade_hit_with_transperant_material`
+    Testing::assert_nearly_eq(color, Color::new(1.11500, 0.69643, 0.69243));
+}
+
+#[test]
+fn default_render_settings_match_constants() {
+    let settings = World::new().settings;
+
+    assert_eq!(settings.max_depth, MAX_REFLECTION_RECRUSTION);
+    assert_eq!(settings.ray_bias, EPSILON);
+    assert_eq!(settings.shadow_bias, EPSILON);
+    assert_eq!(settings.background, color::BLACK);
+    assert_eq!(settings.threads, 1);
+}
+
+#[test]
+fn missing_rays_return_the_background() {
+    let mut w = World::new();
+    w.settings.background = Color::new(0.2, 0.3, 0.4);
+    let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 1.0, 0.0));
+
+    assert_eq!(
+        w.color_at(r, w.settings.max_depth),
+        Color::new(0.2, 0.3, 0.4)
+    );
+}
+
+#[test]
+fn shadow_bias_moves_the_shadow_ray_origin() {
+    let mut w = World::default();
+    let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    let i = Intersection::new(4.0, w.objects[0]);
+    let xs = Intersections {
+        list: vec![i.clone()],
+    };
+    let comps = i.prepare_computations(r, Some(&xs)).unwrap();
+
+    w.settings.shadow_bias = 0.5;
+
+    Testing::assert_nearly_eq(w.shadow_point(&comps), Point::new(0.0, 0.0, -1.5));
+}
+
+#[test]
+fn rendering_with_threads_gives_the_same_image() {
+    let mut w = World::default();
+    let mut c = Camera::new(11, 7, FRAC_PI_2);
+    c.transform = Transformation::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+    let single = c.render(w.clone());
+
+    w.settings.threads = 4;
+    let threaded = c.render(w);
+
+    assert_eq!(single.grid, threaded.grid);
+}
+
+#[test]
+fn adaptive_and_aov_renders_with_threads_give_the_same_images() {
+    let mut w = World::default();
+    let mut c = Camera::new(11, 7, FRAC_PI_2);
+    c.transform = Transformation::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+    c.aperture = 0.2;
+    let aovs = [Aov::Beauty, Aov::Depth];
+    let passes = c.render_aovs(w.clone(), &aovs);
+    c.adaptive = Some(Adaptive::default());
+    let (single, single_counts) = c.render_with_sample_counts(w.clone());
+
+    w.settings.threads = 3;
+    let (threaded, threaded_counts) = c.render_with_sample_counts(w.clone());
+    let threaded_passes = c.render_aovs(w, &aovs);
+
+    assert_eq!(single.grid, threaded.grid);
+    assert_eq!(single_counts.counts, threaded_counts.counts);
+    for aov in aovs {
+        assert_eq!(
+            passes.get(aov).unwrap().grid,
+            threaded_passes.get(aov).unwrap().grid
+        );
+    }
+}
+
+#[test]
+fn tinted_glass_absorbs_over_its_thickness() {
+    let through = |radius: f64| {
+        let mut glass = shape::sphere::glass();
+        glass.transformation = Transformation::scaling(radius, radius, radius);
+        glass.material = Material {
+            color: color::BLACK,
+            ambient: 0.0,
+            diffuse: 0.0,
+            specular: 0.0,
+            refractive_index: 1.0,
+            absorption: Color::new(0.5, 0.5, 1.0),
+            ..glass.material
+        };
+        let mut w = World {
+            objects: vec![glass],
+            ..World::default()
+        };
+        w.settings.background = Color::new(1.0, 1.0, 1.0);
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        w.color_at(r, MAX_REFLECTION_RECRUSTION)
+    };
+
+    Testing::assert_nearly_eq(through(1.0), Color::new(0.25, 0.25, 1.0));
+    Testing::assert_nearly_eq(through(2.0), Color::new(0.0625, 0.0625, 1.0));
+}
+
+#[test]
+fn glass_casts_a_colored_shadow() {
+    let mut glass = shape::sphere::glass();
+    glass.material.absorption = Color::new(0.5, 1.0, 1.0);
+    let w = World {
+        objects: vec![glass],
+        lights: vec![Light::new(
+            Point::new(0.0, 10.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        )],
+        ..World::default()
+    };
+
+    // 2 units of glass between the point and the light
+    let below = Point::new(0.0, -5.0, 0.0);
+    Testing::assert_nearly_eq(
+        w.light_transmittance(&w.lights[0], below, 0.0),
+        Color::new(0.25, 1.0, 1.0),
+    );
+    assert!(!w.is_shadowed(below));
+
+    // beside the sphere
+    let beside = Point::new(2.0, -5.0, 0.0);
+    assert_eq!(
+        w.light_transmittance(&w.lights[0], beside, 0.0),
+        Color::new(1.0, 1.0, 1.0)
+    );
+}
+
+#[test]
+fn partially_transparent_objects_dim_the_light() {
+    let mut w = World::default();
+    w.objects.iter_mut().for_each(|object| {
+        object.material.transparency = 0.5;
+    });
+
+    // behind both spheres, crossing 4 surfaces
+    let p = Point::new(10.0, -10.0, 10.0);
+    Testing::assert_nearly_eq(
+        w.light_transmittance(&w.lights[0], p, 0.0),
+        Color::new(0.0625, 0.0625, 0.0625),
+    );
+}
+
+#[test]
+fn opaque_objects_block_the_light() {
+    let w = World::default();
+    let p = Point::new(10.0, -10.0, 10.0);
+
+    assert_eq!(w.light_transmittance(&w.lights[0], p, 0.0), color::BLACK);
+    // no light, no shadow
+    assert!(!World::new().is_shadowed(p));
+}
+
+#[test]
+fn objects_hidden_from_some_rays() {
+    let mut w = World::default();
+    w.objects[0].visibility.camera = false;
+    let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+    // only the inner sphere
+    assert_eq!(w.intersect(r).count(), 2);
+
+    let reflected = Ray {
+        kind: RayKind::Reflection,
+        ..r
+    };
+    assert_eq!(w.intersect(reflected).count(), 4);
+
+    w.objects[0].visibility.refraction = false;
+    let refracted = Ray {
+        kind: RayKind::Refraction,
+        ..r
+    };
+    assert_eq!(w.intersect(refracted).count(), 2);
+}
+
+#[test]
+fn hidden_from_camera_but_reflected() {
+    let mut w = World::default();
+    w.objects[0].visibility.camera = false;
+    let mut material = Material::default();
+    material.reflective = 0.5;
+    let mirror = shape::plane::new(Transformation::translation(0.0, -1.0, 0.0), material);
+    w.objects.push(mirror);
+
+    let r = Ray::new(
+        Point::new(0.0, 0.0, -3.0),
+        Vector::new(0.0, -SQRT_2 / 2.0, SQRT_2 / 2.0),
+    );
+    let i = Intersection::new(SQRT_2, mirror);
+    let xs = Intersections {
+        list: vec![i.to_owned()],
+    };
+    let comps = i.prepare_computations(r, Some(&xs)).unwrap();
+
+    // same as `reflect_color_for_reflective_material`
+    let c = w.reflected_color(&comps, MAX_REFLECTION_RECRUSTION);
+    Color::assert_nearly_eq(c, Color::new(0.1903306125, 0.237913265737, 0.142747959442));
+
+    w.objects[0].visibility.reflection = false;
+    let c = w.reflected_color(&comps, MAX_REFLECTION_RECRUSTION);
+    assert_eq!(c, color::BLACK);
+}
+
+#[test]
+fn objects_that_cast_no_shadows() {
+    let mut w = World::default();
+    let p = Point::new(10.0, -10.0, 10.0);
+    assert!(w.is_shadowed(p));
+
+    w.objects
+        .iter_mut()
+        .for_each(|object| object.visibility.casts_shadows = false);
+    assert!(!w.is_shadowed(p));
+}
+
+#[test]
+fn objects_that_receive_no_shadows() {
+    // same as `intersection_is_shadow`, without the shadow
+    let mut w = World::new();
+    w.lights = vec![Light::new(
+        Point::new(0.0, 0.0, -10.0),
+        Color::new(1.0, 1.0, 1.0),
+    )];
+    let r = Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0));
+    let s1 = shape::sphere::default();
+    let mut s2 = shape::sphere::new(
+        Transformation::translation(0.0, 0.0, 10.0),
+        Material::default(),
+    );
+    s2.visibility.receives_shadows = false;
+    w.objects = vec![s1, s2];
+
+    let i = Intersection::new(4.0, s2);
+    let xs = Intersections {
+        list: vec![i.to_owned()],
+    };
+    let comps = i.prepare_computations(r, Some(&xs)).unwrap();
+
+    assert_eq!(
+        w.surface_light(&w.lights[0], &comps),
+        Color::new(1.0, 1.0, 1.0)
+    );
+    let c = w.shade_hit(&comps, MAX_REFLECTION_RECRUSTION);
+    assert!(c.red > 0.1);
+}
+
+#[test]
+fn secondary_rays_have_a_kind() {
+    let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    assert_eq!(r.kind, RayKind::Camera);
+
+    let s = shape::sphere::default();
+    let i = Intersection::new(4.0, s);
+    let xs = Intersections {
+        list: vec![i.to_owned()],
+    };
+    let comps = i.prepare_computations(r, Some(&xs)).unwrap();
+    let reflected = comps.secondary_ray(comps.over_point, comps.reflectv, RayKind::Reflection);
+
+    assert_eq!(reflected.kind, RayKind::Reflection);
+}
+
+#[test]
+fn shading_without_lights() {
+    let w = World {
+        lights: vec![],
+        ..World::default()
+    };
+    let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+    assert_eq!(w.color_at(r, MAX_REFLECTION_RECRUSTION), color::BLACK);
+}
+
+#[test]
+fn lights_add_up() {
+    let mut w = World::default();
+    let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    let one = w.color_at(r, MAX_REFLECTION_RECRUSTION);
+
+    w.lights.push(w.lights[0]);
+    let two = w.color_at(r, MAX_REFLECTION_RECRUSTION);
+
+    Testing::assert_nearly_eq(two, one * 2.0);
+}
+
+#[test]
+fn every_light_has_its_own_shadow() {
+    let mut w = World::default();
+    // behind the spheres from the default light, in the open from this one
+    w.lights.push(Light::new(
+        Point::new(10.0, 10.0, 10.0),
+        Color::new(1.0, 1.0, 1.0),
+    ));
+    let p = Point::new(10.0, -10.0, 10.0);
+
+    assert_eq!(w.light_transmittance(&w.lights[0], p, 0.0), color::BLACK);
+    assert_eq!(
+        w.light_transmittance(&w.lights[1], p, 0.0),
+        Color::new(1.0, 1.0, 1.0)
+    );
+    // shadowed only when no light gets through
+    assert!(!w.is_shadowed(p));
+}
+
+#[test]
+fn light_attenuation_is_averaged_over_the_lights() {
+    // `intersection_is_shadow`, with a second light that isn't blocked
+    let mut w = World::new();
+    w.lights = vec![
+        Light::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0)),
+        Light::new(Point::new(0.0, 0.0, 5.0), Color::new(1.0, 1.0, 1.0)),
+    ];
+    let r = Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0));
+    let s2 = shape::sphere::new(
+        Transformation::translation(0.0, 0.0, 10.0),
+        Material::default(),
+    );
+    w.objects = vec![shape::sphere::default(), s2];
+
+    let i = Intersection::new(4.0, s2);
+    let xs = Intersections {
+        list: vec![i.to_owned()],
+    };
+    let comps = i.prepare_computations(r, Some(&xs)).unwrap();
+    let shading = w.shade_hit_components(&comps, MAX_REFLECTION_RECRUSTION);
+
+    assert_eq!(shading.light_attenuation, Color::new(0.5, 0.5, 0.5));
+}
+
+#[test]
+fn area_light_casts_a_penumbra() {
+    let mut w = World::default();
+    // default light grown to a 4x4 square
+    w.lights[0] = Light::rectangle(
+        Point::new(-10.0, 10.0, -10.0),
+        Vector::new(4.0, 0.0, 0.0),
+        Vector::new(0.0, 4.0, 0.0),
+        Color::new(1.0, 1.0, 1.0),
+        16,
+    );
+    let light = w.lights[0];
+
+    // in the open
+    let lit = Point::new(0.0, 10.0, 0.0);
+    assert_eq!(
+        w.light_transmittance(&light, lit, 0.0),
+        Color::new(1.0, 1.0, 1.0)
+    );
+    // right behind the outer sphere
+    let umbra = Point::new(0.9, -0.9, 0.9);
+    assert_eq!(w.light_transmittance(&light, umbra, 0.0), color::BLACK);
+    // far behind, the spheres only hide part of the light
+    let penumbra = Point::new(10.0, -10.0, 10.0);
+    let partial = w.light_transmittance(&light, penumbra, 0.0);
+    assert!(partial.red > 0.0 && partial.red < 1.0);
+    assert_eq!(partial.red, partial.green);
+}
+
+#[test]
+fn directional_light_casts_parallel_shadows() {
+    let mut w = World::default();
+    // same direction as the default light, from infinitely far away
+    w.lights[0] = Light::directional(Vector::new(1.0, -1.0, 1.0), Color::new(1.0, 1.0, 1.0));
+    let light = w.lights[0];
+
+    // far beyond the default light, still in the spheres' shadow
+    assert_eq!(
+        w.light_transmittance(&light, Point::new(20.0, -20.0, 20.0), 0.0),
+        color::BLACK
+    );
+    // also lit when between the old light position and the spheres
+    assert_eq!(
+        w.light_transmittance(&light, Point::new(-20.0, 20.0, -20.0), 0.0),
+        Color::new(1.0, 1.0, 1.0)
+    );
+    assert!(w.is_shadowed(Point::new(2.0, -2.0, 2.0)));
+}
+
+#[test]
+fn spot_light_only_lights_its_cone() {
+    let mut w = World::new();
+    w.objects = vec![shape::plane::default()];
+    w.lights = vec![Light::spot(
+        Point::new(0.0, 5.0, 0.0),
+        Vector::new(0.0, -1.0, 0.0),
+        0.3,
+        0.5,
+        Color::new(1.0, 1.0, 1.0),
+    )];
+    let down = Vector::new(0.0, -1.0, 0.0);
+
+    let inside = w.color_at(Ray::new(Point::new(0.0, 1.0, 0.0), down), 5);
+    let outside = w.color_at(Ray::new(Point::new(10.0, 1.0, 0.0), down), 5);
+    assert!(inside.red > 0.5);
+    assert_eq!(outside, color::BLACK);
+}
+
+#[test]
+fn shading_uses_the_shadow_bias() {
+    let mut w = World::default();
+    w.settings.shadow_bias = 0.5;
+    let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    let shape = w.objects[0];
+    let i = Intersection::new(4.0, shape);
+    let xs = Intersections {
+        list: vec![i.to_owned()],
+    };
+    let comps = i.prepare_computations(r, Some(&xs)).unwrap();
+
+    let lit_at = |position| {
+        shape.material.lighting(
+            shape,
+            w.lights[0],
+            position,
+            comps.eyev,
+            comps.normalv,
+            color::WHITE,
+        )
+    };
+    let c = w.shade_hit(&comps, MAX_REFLECTION_RECRUSTION);
+
+    assert_eq!(c, lit_at(comps.point + comps.normalv * 0.5));
+    assert_ne!(c, lit_at(comps.over_point));
 }